@@ -55,6 +55,7 @@
             name = "all";
             paths = with crates; [
               sarif-fmt.crane.outputs.drv.crate
+              sarif-cli.crane.outputs.drv.crate
               clippy-sarif.crane.outputs.drv.crate
               hadolint-sarif.crane.outputs.drv.crate
               miri-sarif.crane.outputs.drv.crate
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use clap::Args;
+use serde_sarif::sarif;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub(crate) struct BaselineArgs {
+  /// SARIF file of a previous analysis to compare against
+  #[arg(short, long)]
+  baseline: PathBuf,
+  /// input file; reads from stdin if none is given
+  #[arg(short, long)]
+  input: Option<PathBuf>,
+  /// output file; writes to stdout if none is given
+  #[arg(short, long)]
+  output: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: BaselineArgs) -> Result<()> {
+  let baseline: sarif::Sarif =
+    serde_json::from_reader(crate::io::reader(Some(&args.baseline))?)?;
+  let current: sarif::Sarif =
+    serde_json::from_reader(crate::io::reader(args.input.as_deref())?)?;
+
+  let compared = serde_sarif::baseline::compare(&baseline, current);
+
+  let writer = crate::io::writer(args.output.as_deref())?;
+  serde_json::to_writer_pretty(writer, &compared)?;
+  Ok(())
+}
@@ -0,0 +1,131 @@
+#![doc(html_root_url = "https://docs.rs/sarif-cli/0.8.0")]
+
+//! This crate provides a single command line tool, `sarif`, which bundles the
+//! converters (`clippy-sarif`, `miri-sarif`, `hadolint-sarif`,
+//! `shellcheck-sarif`, `clang-tidy-sarif`), the pretty printer (`sarif-fmt`)
+//! and utilities to process SARIF files as subcommands.
+//!
+//! The latest [documentation can be found here](https://docs.rs/sarif_cli).
+//!
+//! SARIF or the Static Analysis Results Interchange Format is an industry
+//! standard format for the output of static analysis tools. More information
+//! can be found on the official website: [https://sarifweb.azurewebsites.net/](https://sarifweb.azurewebsites.net/).
+//!
+//! ## Installation
+//!
+//! `sarif-cli` may be installed via `cargo`
+//!
+//! ```shell
+//! cargo install sarif-cli
+//! ```
+//!
+//! or downloaded directly from Github Releases
+//!
+//!```shell
+//! # make sure to adjust the target and version (you may also want to pin to a specific version)
+//! curl -sSL https://github.com/psastras/sarif-rs/releases/download/sarif-cli-latest/sarif-x86_64-unknown-linux-gnu -o sarif
+//! ```
+//!
+//! ## Usage
+//!
+//! Tool output is converted to SARIF with `sarif convert <tool>`, which accepts
+//! the same arguments as the corresponding standalone converter. SARIF is
+//! pretty printed with `sarif fmt`, which accepts the same arguments as
+//! `sarif-fmt`.
+//!
+//! ## Example
+//!
+//!```shell
+//! $ cargo clippy --message-format=json | sarif convert clippy | sarif fmt
+//! $ shellcheck -f json1 shellscript.sh | sarif convert shellcheck --format json1
+//! $ hadolint -f json Dockerfile | sarif convert hadolint -o hadolint.sarif
+//! ```
+//!
+//! Multiple SARIF files may be combined into a single file with `sarif merge`.
+//! Passing `--coalesce` combines runs of the same tool into a single run.
+//!
+//!```shell
+//! $ sarif merge --coalesce clippy.sarif shellcheck.sarif -o results.sarif
+//! ```
+//!
+//! A SARIF file may be compared against the SARIF file of a previous analysis
+//! with `sarif baseline`, which marks every result as `new`, `unchanged` or
+//! `updated`, and adds the results which are no longer reported as `absent`.
+//!
+//!```shell
+//! $ sarif baseline --baseline main.sarif -i current.sarif -o results.sarif
+//! ```
+//!
+//! `sarif validate` checks a SARIF file against the SARIF 2.1.0 schema and
+//! specification, printing a JSON pointer to every offending node to stderr
+//! (as `sarif-fmt --validate` does) and failing if any is found.
+//!
+//!```shell
+//! $ sarif validate -i results.sarif
+//! ```
+//!
+//! `sarif fix` applies the fixes of the results of a SARIF file (ex. the
+//! suggestions of clippy) to the files they refer to. Fixes which overlap are
+//! skipped with a warning, and so are suggestions which rustc does not mark as
+//! `MachineApplicable`, unless `--applicability` allows less certain ones (ex.
+//! `--applicability MaybeIncorrect`). Passing `--dry-run` prints the changes as
+//! a unified diff instead, with paths relative to the current directory.
+//!
+//!```shell
+//! $ sarif fix --dry-run -i results.sarif
+//! ```
+//!
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+mod baseline;
+mod convert;
+mod fix;
+mod io;
+mod merge;
+mod validate;
+
+#[derive(Parser, Debug)]
+#[command(
+  version,
+  about = "Convert, view and process SARIF files",
+  long_about = None,
+)]
+struct Args {
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+  /// Compare a SARIF file against a baseline, setting the baselineState of
+  /// every result
+  Baseline(baseline::BaselineArgs),
+  /// Convert the output of a tool into SARIF
+  Convert {
+    #[command(subcommand)]
+    tool: convert::Tool,
+  },
+  /// Apply the fixes of SARIF results to the files they refer to
+  Fix(fix::FixArgs),
+  /// Pretty print SARIF results
+  Fmt(sarif_fmt::Args),
+  /// Merge multiple SARIF files into a single file
+  Merge(merge::MergeArgs),
+  /// Check a SARIF file against the SARIF schema and specification
+  Validate(validate::ValidateArgs),
+}
+
+fn main() -> Result<()> {
+  let args = Args::parse();
+
+  match args.command {
+    Command::Baseline(args) => baseline::run(args),
+    Command::Convert { tool } => convert::run(tool),
+    Command::Fix(args) => fix::run(args),
+    Command::Fmt(args) => sarif_fmt::run(args),
+    Command::Merge(args) => merge::run(args),
+    Command::Validate(args) => validate::run(args),
+  }
+}
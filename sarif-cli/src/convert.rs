@@ -0,0 +1,100 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use serde_sarif::converters::{
+  clang_tidy::ClangTidyConverter,
+  clippy::ClippyConverter,
+  hadolint::HadolintConverter,
+  miri::MiriConverter,
+  shellcheck::{ShellcheckConverter, ShellcheckFormat, ShellcheckOptions},
+  Converter, ConverterArgs,
+};
+use std::str::FromStr;
+
+#[derive(Args, Debug)]
+pub(crate) struct ConvertArgs {
+  /// input file; reads from stdin if none is given
+  #[arg(short, long)]
+  input: Option<std::path::PathBuf>,
+  /// output file; writes to stdout if none is given
+  #[arg(short, long)]
+  output: Option<std::path::PathBuf>,
+  #[command(flatten)]
+  converter: ConverterArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Tool {
+  /// Convert clang-tidy output into SARIF
+  #[command(
+    after_help = "The expected input is generated by running 'clang-tidy'."
+  )]
+  ClangTidy(ConvertArgs),
+  /// Convert clippy output into SARIF
+  #[command(
+    after_help = "The expected input is generated by running 'cargo clippy --message-format=json'."
+  )]
+  Clippy(ConvertArgs),
+  /// Convert hadolint warnings into SARIF
+  #[command(
+    after_help = "The expected input is generated by running 'hadolint -f json'."
+  )]
+  Hadolint(ConvertArgs),
+  /// Convert miri output into SARIF
+  #[command(
+    after_help = "The expected input is generated by running 'MIRIFLAGS=\"--error-format=json\" cargo miri test'."
+  )]
+  Miri(ConvertArgs),
+  /// Convert shellcheck warnings into SARIF
+  #[command(
+    after_help = "The expected input is generated by running 'shellcheck -f json'."
+  )]
+  Shellcheck {
+    #[command(flatten)]
+    args: ConvertArgs,
+    /// input format; json or json1
+    #[arg(
+      short,
+      long,
+      default_value = "json",
+      value_parser = ShellcheckFormat::from_str
+    )]
+    format: ShellcheckFormat,
+  },
+}
+
+// Returns the converter of `tool`, with the arguments it was given
+fn converter(tool: Tool) -> Result<(Box<dyn Converter>, ConvertArgs)> {
+  Ok(match tool {
+    Tool::ClangTidy(args) => (
+      Box::new(ClangTidyConverter::new(args.converter.options()?)),
+      args,
+    ),
+    Tool::Clippy(args) => (
+      Box::new(ClippyConverter::new(args.converter.options()?)),
+      args,
+    ),
+    Tool::Hadolint(args) => (
+      Box::new(HadolintConverter::new(args.converter.options()?)),
+      args,
+    ),
+    Tool::Miri(args) => (
+      Box::new(MiriConverter::new(args.converter.options()?)),
+      args,
+    ),
+    Tool::Shellcheck { args, format } => {
+      let options = ShellcheckOptions::builder()
+        .format(format)
+        .common(args.converter.options()?)
+        .build();
+      (Box::new(ShellcheckConverter::new(options)), args)
+    }
+  })
+}
+
+pub(crate) fn run(tool: Tool) -> Result<()> {
+  let (converter, args) = converter(tool)?;
+  converter.convert_to_writer(
+    &mut crate::io::reader(args.input.as_deref())?,
+    &mut crate::io::writer(args.output.as_deref())?,
+  )
+}
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use clap::Args;
+use serde_sarif::fix::Applicability;
+use serde_sarif::sarif;
+use similar::TextDiff;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub(crate) struct FixArgs {
+  /// input file; reads from stdin if none is given
+  #[arg(short, long)]
+  input: Option<PathBuf>,
+  /// print the changes as a unified diff instead of writing them to the files
+  #[arg(long)]
+  dry_run: bool,
+  /// also apply fixes which are less likely to be correct, up to this
+  /// applicability of rustc suggestions (MachineApplicable, MaybeIncorrect,
+  /// HasPlaceholders or Unspecified)
+  #[arg(long, default_value_t = Applicability::MachineApplicable)]
+  applicability: Applicability,
+}
+
+// Returns the old and new file headers of the diff of `path`, which is made
+// relative to `current_dir` first; absolute paths outside of it are kept as
+// they are, without the `a/` and `b/` prefixes
+fn headers(path: &Path, current_dir: &Path) -> (String, String) {
+  let path = match path.strip_prefix(current_dir) {
+    Ok(path) => path,
+    Err(_) if path.is_relative() => path,
+    Err(_) => {
+      let path = path.to_string_lossy();
+      return (path.to_string(), path.to_string());
+    }
+  };
+  let path = path.to_string_lossy();
+  (format!("a/{}", path), format!("b/{}", path))
+}
+
+pub(crate) fn run(args: FixArgs) -> Result<()> {
+  let sarif: sarif::Sarif =
+    serde_json::from_reader(crate::io::reader(args.input.as_deref())?)?;
+
+  let plan = serde_sarif::fix::collect_fixes(&sarif, args.applicability);
+  plan
+    .skipped
+    .iter()
+    .for_each(|error| eprintln!("warning: skipping fix: {}", error));
+
+  if args.dry_run {
+    let mut writer = crate::io::writer(None)?;
+    let current_dir = std::env::current_dir()?;
+    plan.files.iter().try_for_each(|file| {
+      let (old, new) = headers(&file.path, &current_dir);
+      let edited = file.edited();
+      write!(
+        writer,
+        "{}",
+        TextDiff::from_lines(&file.original, &edited)
+          .unified_diff()
+          .header(&old, &new)
+      )
+    })?;
+  } else {
+    plan.apply()?;
+    eprintln!("fixed {} results in {} files", plan.fixed, plan.files.len());
+  }
+  Ok(())
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Returns a reader of the file at `path`; reads from stdin if none is given
+pub(crate) fn reader(path: Option<&Path>) -> Result<BufReader<Box<dyn Read>>> {
+  let read = match path {
+    Some(path) => Box::new(File::open(path)?) as Box<dyn Read>,
+    None => Box::new(std::io::stdin()) as Box<dyn Read>,
+  };
+  Ok(BufReader::new(read))
+}
+
+/// Returns a writer to the file at `path`; writes to stdout if none is given
+pub(crate) fn writer(path: Option<&Path>) -> Result<BufWriter<Box<dyn Write>>> {
+  let write = match path {
+    Some(path) => Box::new(File::create(path)?) as Box<dyn Write>,
+    None => Box::new(std::io::stdout()) as Box<dyn Write>,
+  };
+  Ok(BufWriter::new(write))
+}
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use clap::Args;
+use serde_sarif::merge::MergeOptions;
+use serde_sarif::sarif;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub(crate) struct MergeArgs {
+  /// SARIF files to merge
+  #[arg(required = true)]
+  inputs: Vec<PathBuf>,
+  /// output file; writes to stdout if none is given
+  #[arg(short, long)]
+  output: Option<PathBuf>,
+  /// Combine runs of the same tool (same tool.driver.name) into a single run
+  #[arg(long)]
+  coalesce: bool,
+}
+
+pub(crate) fn run(args: MergeArgs) -> Result<()> {
+  let logs = args
+    .inputs
+    .iter()
+    .map(|path| -> Result<sarif::Sarif> {
+      let reader = crate::io::reader(Some(path))?;
+      Ok(serde_json::from_reader(reader)?)
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+  let options = MergeOptions::builder().coalesce_runs(args.coalesce).build();
+  let merged = serde_sarif::merge::merge(logs, &options);
+
+  let writer = crate::io::writer(args.output.as_deref())?;
+  serde_json::to_writer_pretty(writer, &merged)?;
+  Ok(())
+}
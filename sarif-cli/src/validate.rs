@@ -0,0 +1,17 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub(crate) struct ValidateArgs {
+  /// input file; reads from stdin if none is given
+  #[arg(short, long)]
+  input: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: ValidateArgs) -> Result<()> {
+  let log: serde_json::Value =
+    serde_json::from_reader(crate::io::reader(args.input.as_deref())?)?;
+
+  sarif_fmt::validate_log(&log)
+}
@@ -0,0 +1,341 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+// Returns the root directory of the workspace
+fn cargo_workspace_directory() -> Result<PathBuf> {
+  Ok(fs::canonicalize(
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."),
+  )?)
+}
+
+// Returns the path of the sarif binary, after building it
+fn sarif_bin() -> Result<PathBuf> {
+  let cargo_workspace_directory = cargo_workspace_directory()?;
+  duct_sh::sh("cargo build --bin sarif")
+    .dir(&cargo_workspace_directory)
+    .run()?;
+  Ok(fs::canonicalize(
+    cargo_workspace_directory.join("target/debug/sarif"),
+  )?)
+}
+
+#[test]
+// Test that converting and formatting through the single binary works
+fn test_convert_and_fmt() -> Result<()> {
+  let cargo_workspace_directory = cargo_workspace_directory()?;
+  let sarif_bin = sarif_bin()?;
+
+  let hadolint_output = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./sarif-fmt/tests/data/hadolint.out"),
+    ]
+    .iter(),
+  ))?;
+
+  let cmd = format!(
+    "{} convert hadolint -i {} | {} fmt",
+    sarif_bin.to_str().unwrap(),
+    hadolint_output.to_str().unwrap(),
+    sarif_bin.to_str().unwrap(),
+  );
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+  let output = duct_sh::sh_dangerous(cmd.as_str())
+    .dir(cargo_workspace_directory)
+    .unchecked()
+    .full_env(&env_map)
+    .read()?;
+
+  assert!(
+    output.contains("warning: Always tag the version of an image explicitly")
+  );
+  assert!(output.contains("Dockerfile:1:1"));
+  assert!(output.contains("FROM debian"));
+  assert!(output.contains("DL3006"));
+
+  Ok(())
+}
+
+#[test]
+// Test that converter specific options are forwarded to the converter
+fn test_convert_shellcheck_json1() -> Result<()> {
+  let sarif_bin = sarif_bin()?;
+
+  let output = duct::cmd!(sarif_bin, "convert", "shellcheck", "-f", "json1")
+    .stdin_bytes(
+      r#"{"comments":[{"file":"shell.sh","line":1,"endLine":1,"column":1,"endColumn":2,"level":"error","code":1073,"message":"Couldn't parse this for loop."}]}"#,
+    )
+    .read()?;
+
+  assert!(output.contains(r#""ruleId": "1073""#));
+  assert!(output.contains(r#""name": "shellcheck""#));
+
+  Ok(())
+}
+
+#[test]
+// Test that --fingerprints populates the partial fingerprints of every result
+fn test_convert_fingerprints() -> Result<()> {
+  let cargo_workspace_directory = cargo_workspace_directory()?;
+  let sarif_bin = sarif_bin()?;
+
+  let hadolint_output = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./sarif-fmt/tests/data/hadolint.out"),
+    ]
+    .iter(),
+  ))?;
+
+  let output = duct::cmd!(
+    sarif_bin,
+    "convert",
+    "hadolint",
+    "--fingerprints",
+    "-i",
+    hadolint_output
+  )
+  .dir(cargo_workspace_directory.join("sarif-fmt/tests/data"))
+  .read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  let results = sarif["runs"][0]["results"].as_array().unwrap();
+
+  assert!(!results.is_empty());
+  assert!(results.iter().all(|result| result["partialFingerprints"]
+    ["primaryLocationLineHash"]
+    .is_string()));
+
+  Ok(())
+}
+
+#[test]
+// Test that clippy suggestions are converted into fixes
+fn test_convert_clippy_fixes() -> Result<()> {
+  let cargo_workspace_directory = cargo_workspace_directory()?;
+  let sarif_bin = sarif_bin()?;
+
+  let clippy_output = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./sarif-fmt/tests/data/clippy.out"),
+    ]
+    .iter(),
+  ))?;
+
+  let output =
+    duct::cmd!(sarif_bin, "convert", "clippy", "-i", clippy_output).read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  let fix = &sarif["runs"][0]["results"][0]["fixes"][0];
+  let artifact_change = &fix["artifactChanges"][0];
+
+  assert_eq!(fix["properties"]["applicability"], "MachineApplicable");
+  assert_eq!(artifact_change["artifactLocation"]["uri"], "src/main.rs");
+  assert_eq!(
+    artifact_change["replacements"][0]["deletedRegion"]["byteOffset"],
+    50
+  );
+  assert_eq!(
+    artifact_change["replacements"][0]["insertedContent"]["text"],
+    "vec.len() <= 0;"
+  );
+
+  Ok(())
+}
+
+#[test]
+// Test that shellcheck replacements are converted into a single ordered fix
+fn test_convert_shellcheck_fixes() -> Result<()> {
+  let sarif_bin = sarif_bin()?;
+
+  let output = duct::cmd!(sarif_bin, "convert", "shellcheck", "-f", "json1")
+    .stdin_bytes(
+      r#"{"comments":[{"file":"t.sh","line":2,"endLine":2,"column":6,"endColumn":10,"level":"info","code":2086,"message":"Double quote to prevent globbing and word splitting.","fix":{"replacements":[
+        {"line":2,"endLine":2,"column":10,"endColumn":10,"insertionPoint":"beforeStart","precedence":7,"replacement":"\""},
+        {"line":2,"endLine":2,"column":8,"endColumn":8,"insertionPoint":"afterEnd","precedence":7,"replacement":"\""},
+        {"line":2,"endLine":2,"column":8,"endColumn":8,"insertionPoint":"beforeStart","precedence":7,"replacement":"\""},
+        {"line":2,"endLine":2,"column":6,"endColumn":6,"insertionPoint":"afterEnd","precedence":7,"replacement":"\""}
+      ]}}]}"#,
+    )
+    .read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  let result = &sarif["runs"][0]["results"][0];
+  let fixes = result["fixes"].as_array().unwrap();
+  let replacements: Vec<(i64, &str)> = fixes[0]["artifactChanges"][0]
+    ["replacements"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|replacement| {
+      (
+        replacement["deletedRegion"]["startColumn"]
+          .as_i64()
+          .unwrap(),
+        replacement["insertedContent"]["text"].as_str().unwrap(),
+      )
+    })
+    .collect();
+
+  assert_eq!(fixes.len(), 1);
+  assert_eq!(replacements, vec![(6, "\""), (8, "\"\""), (10, "\"")]);
+  assert!(result["relatedLocations"].is_null());
+
+  Ok(())
+}
+
+#[test]
+// Test that --artifacts describes the files referenced by results
+fn test_convert_artifacts() -> Result<()> {
+  let cargo_workspace_directory = cargo_workspace_directory()?;
+  let sarif_bin = sarif_bin()?;
+
+  let data_directory = cargo_workspace_directory.join("sarif-fmt/tests/data");
+  let output = duct::cmd!(
+    sarif_bin,
+    "convert",
+    "clippy",
+    "--artifacts",
+    "-i",
+    data_directory.join("clippy.out")
+  )
+  .dir(&data_directory)
+  .read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  let run = &sarif["runs"][0];
+  let artifacts = run["artifacts"].as_array().unwrap();
+
+  assert_eq!(artifacts.len(), 1);
+  assert_eq!(artifacts[0]["location"]["uri"], "src/main.rs");
+  assert_eq!(
+    artifacts[0]["length"],
+    fs::metadata(data_directory.join("src/main.rs"))?.len()
+  );
+  assert_eq!(artifacts[0]["encoding"], "utf-8");
+  assert_eq!(artifacts[0]["mimeType"], "text/x-rust");
+  assert!(artifacts[0]["hashes"]["sha-256"].is_string());
+  assert_eq!(
+    run["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]
+      ["index"],
+    0
+  );
+
+  Ok(())
+}
+
+#[test]
+// Test that --src-root makes URIs relative to the root of the sources
+fn test_convert_src_root() -> Result<()> {
+  let cargo_workspace_directory = cargo_workspace_directory()?;
+  let sarif_bin = sarif_bin()?;
+
+  let data_directory = cargo_workspace_directory.join("sarif-fmt/tests/data");
+  let output = duct::cmd!(
+    sarif_bin,
+    "convert",
+    "clippy",
+    "--artifacts",
+    "--src-root",
+    &cargo_workspace_directory,
+    "-i",
+    data_directory.join("clippy.out")
+  )
+  .dir(&data_directory)
+  .read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  let run = &sarif["runs"][0];
+  let artifact_location =
+    &run["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"];
+
+  assert_eq!(artifact_location["uri"], "sarif-fmt/tests/data/src/main.rs");
+  assert_eq!(artifact_location["uriBaseId"], "%SRCROOT%");
+  assert_eq!(
+    run["artifacts"][0]["location"]["uri"],
+    "sarif-fmt/tests/data/src/main.rs"
+  );
+  assert!(run["artifacts"][0]["hashes"]["sha-256"].is_string());
+  let src_root = run["originalUriBaseIds"]["%SRCROOT%"]["uri"]
+    .as_str()
+    .unwrap();
+  assert!(src_root.starts_with("file:///"));
+  assert!(src_root.ends_with('/'));
+
+  Ok(())
+}
+
+#[test]
+// Test that converters declare their column kind, and convert the columns of
+// tools which count bytes or expand tabs into code points
+fn test_convert_column_kind() -> Result<()> {
+  let cargo_workspace_directory = cargo_workspace_directory()?;
+  let sarif_bin = sarif_bin()?;
+
+  let tempdir = tempfile::tempdir()?;
+  let directory = tempdir.path();
+  let line = "int f(const char *s) { return atoi(\"é\") + atoi(s); }";
+  fs::write(directory.join("unicode.cpp"), format!("{}\n", line))?;
+  fs::write(directory.join("tabs.sh"), "#!/bin/sh\n\techo $1\n")?;
+
+  // clang-tidy reports the byte column of the second call
+  let byte_column = line.rfind("atoi(s)").unwrap() + 1;
+  let output = duct::cmd!(&sarif_bin, "convert", "clang-tidy")
+    .stdin_bytes(format!(
+      "unicode.cpp:1:{}: warning: 'atoi' used to convert a string to an integer value [cert-err34-c]\n",
+      byte_column
+    ))
+    .dir(directory)
+    .read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  let run = &sarif["runs"][0];
+  assert_eq!(run["columnKind"], "unicodeCodePoints");
+  assert_eq!(
+    run["results"][0]["locations"][0]["physicalLocation"]["region"]
+      ["startColumn"],
+    byte_column as i64 - 1
+  );
+
+  // shellcheck -f json expands the tab before `$1` to 8 columns
+  let output = duct::cmd!(&sarif_bin, "convert", "shellcheck", "-f", "json")
+    .stdin_bytes(
+      r#"[{"file":"tabs.sh","line":2,"endLine":2,"column":14,"endColumn":16,"level":"info","code":2086,"message":"Double quote to prevent globbing and word splitting."}]"#,
+    )
+    .dir(directory)
+    .read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  let run = &sarif["runs"][0];
+  let region = &run["results"][0]["locations"][0]["physicalLocation"]["region"];
+  assert_eq!(run["columnKind"], "unicodeCodePoints");
+  assert_eq!(region["startColumn"], 7);
+  assert_eq!(region["endColumn"], 9);
+
+  // shellcheck -f json1 counts the tab as a single column
+  let output = duct::cmd!(&sarif_bin, "convert", "shellcheck", "-f", "json1")
+    .stdin_bytes(
+      r#"{"comments":[{"file":"tabs.sh","line":2,"endLine":2,"column":7,"endColumn":9,"level":"info","code":2086,"message":"Double quote to prevent globbing and word splitting."}]}"#,
+    )
+    .dir(directory)
+    .read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  let run = &sarif["runs"][0];
+  let region = &run["results"][0]["locations"][0]["physicalLocation"]["region"];
+  assert_eq!(run["columnKind"], "unicodeCodePoints");
+  assert_eq!(region["startColumn"], 7);
+  assert_eq!(region["endColumn"], 9);
+
+  // rustc already counts columns in code points
+  let output = duct::cmd!(
+    &sarif_bin,
+    "convert",
+    "clippy",
+    "-i",
+    cargo_workspace_directory.join("sarif-fmt/tests/data/clippy.out")
+  )
+  .read()?;
+  let sarif: serde_json::Value = serde_json::from_str(&output)?;
+  assert_eq!(sarif["runs"][0]["columnKind"], "unicodeCodePoints");
+
+  Ok(())
+}
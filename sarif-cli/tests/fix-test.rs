@@ -0,0 +1,79 @@
+use anyhow::Result;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+const LOG: &str = r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"clippy"}},"results":[
+  {"message":{"text":"unneeded `return` statement"},"fixes":[{"artifactChanges":[{"artifactLocation":{"uri":"lib.rs"},"replacements":[
+    {"deletedRegion":{"startLine":2,"startColumn":3,"endColumn":11},"insertedContent":{"text":"1"}}
+  ]}]}]},
+  {"message":{"text":"overlapping"},"fixes":[{"artifactChanges":[{"artifactLocation":{"uri":"lib.rs"},"replacements":[
+    {"deletedRegion":{"startLine":2}}
+  ]}]}]}
+]}]}"#;
+
+#[test]
+// Test that fixes are printed as a diff with --dry-run, and applied otherwise
+fn test_fix() -> Result<()> {
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let cargo_workspace_directory = fs::canonicalize(PathBuf::from_iter(
+    [cargo_manifest_directory.clone(), PathBuf::from("..")].iter(),
+  ))?;
+
+  duct_sh::sh("cargo build --bin sarif")
+    .dir(cargo_workspace_directory.clone())
+    .run()?;
+
+  let sarif_bin = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./target/debug/sarif"),
+    ]
+    .iter(),
+  ))?;
+
+  let directory = std::env::temp_dir().join("sarif-cli-fix-test");
+  fs::create_dir_all(&directory)?;
+  let directory = fs::canonicalize(directory)?;
+  let source = directory.join("lib.rs");
+  fs::write(&source, "fn one() -> i32 {\n  return 1;\n}\n")?;
+
+  let output = duct::cmd!(&sarif_bin, "fix", "--dry-run")
+    .dir(&directory)
+    .stdin_bytes(LOG)
+    .stdout_capture()
+    .stderr_capture()
+    .run()?;
+  let stdout = String::from_utf8(output.stdout)?;
+  let stderr = String::from_utf8(output.stderr)?;
+  assert!(stdout.contains("--- a/lib.rs\n+++ b/lib.rs\n"));
+  assert!(stdout.contains("-  return 1;\n+  1;\n"));
+  assert!(stderr.contains("lib.rs: fix overlaps the fix of another result"));
+  assert_eq!(
+    fs::read_to_string(&source)?,
+    "fn one() -> i32 {\n  return 1;\n}\n"
+  );
+
+  // absolute paths are printed relative to the current directory
+  let log = LOG.replace(
+    r#""uri":"lib.rs""#,
+    &format!(r#""uri":"file://{}""#, source.display()),
+  );
+  let stdout = duct::cmd!(&sarif_bin, "fix", "--dry-run")
+    .dir(&directory)
+    .stdin_bytes(log)
+    .stderr_null()
+    .read()?;
+  assert!(stdout.contains("--- a/lib.rs\n+++ b/lib.rs\n"));
+
+  duct::cmd!(&sarif_bin, "fix")
+    .dir(&directory)
+    .stdin_bytes(LOG)
+    .stderr_null()
+    .run()?;
+  assert_eq!(fs::read_to_string(&source)?, "fn one() -> i32 {\n  1;\n}\n");
+
+  fs::remove_dir_all(&directory)?;
+  Ok(())
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+#[test]
+// Test that converted logs of the same tool are coalesced into a single run
+fn test_merge_coalesce() -> Result<()> {
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let cargo_workspace_directory = fs::canonicalize(PathBuf::from_iter(
+    [cargo_manifest_directory.clone(), PathBuf::from("..")].iter(),
+  ))?;
+
+  duct_sh::sh("cargo build --bin sarif")
+    .dir(cargo_workspace_directory.clone())
+    .run()?;
+
+  let sarif_bin = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./target/debug/sarif"),
+    ]
+    .iter(),
+  ))?;
+
+  let hadolint_output = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./sarif-fmt/tests/data/hadolint.out"),
+    ]
+    .iter(),
+  ))?;
+
+  let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+  let hadolint_sarif = out_dir.join("merge-test-hadolint.sarif");
+  duct::cmd!(
+    &sarif_bin,
+    "convert",
+    "hadolint",
+    "-i",
+    &hadolint_output,
+    "-o",
+    &hadolint_sarif
+  )
+  .run()?;
+
+  let output =
+    duct::cmd!(&sarif_bin, "merge", &hadolint_sarif, &hadolint_sarif).read()?;
+  let merged: serde_json::Value = serde_json::from_str(&output)?;
+  assert_eq!(merged["runs"].as_array().unwrap().len(), 2);
+
+  let output = duct::cmd!(
+    &sarif_bin,
+    "merge",
+    "--coalesce",
+    &hadolint_sarif,
+    &hadolint_sarif
+  )
+  .read()?;
+  let merged: serde_json::Value = serde_json::from_str(&output)?;
+  let runs = merged["runs"].as_array().unwrap();
+  assert_eq!(runs.len(), 1);
+  assert_eq!(runs[0]["results"].as_array().unwrap().len(), 16);
+  assert_eq!(
+    runs[0]["tool"]["driver"]["rules"].as_array().unwrap().len(),
+    8
+  );
+
+  Ok(())
+}
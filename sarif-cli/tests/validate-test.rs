@@ -0,0 +1,55 @@
+use anyhow::Result;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+#[test]
+// Test that violations are reported with JSON pointers and fail the command
+fn test_validate() -> Result<()> {
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let cargo_workspace_directory = fs::canonicalize(PathBuf::from_iter(
+    [cargo_manifest_directory.clone(), PathBuf::from("..")].iter(),
+  ))?;
+
+  duct_sh::sh("cargo build --bin sarif")
+    .dir(cargo_workspace_directory.clone())
+    .run()?;
+
+  let sarif_bin = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./target/debug/sarif"),
+    ]
+    .iter(),
+  ))?;
+
+  let output = duct::cmd!(&sarif_bin, "validate")
+    .stdin_bytes(
+      r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"clippy"}},"results":[{"message":{"text":"oops"}}]}]}"#,
+    )
+    .stdout_capture()
+    .unchecked()
+    .run()?;
+  assert!(output.status.success());
+
+  let output = duct::cmd!(&sarif_bin, "validate")
+    .stdin_bytes(
+      r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"clippy"}},"results":[{"message":{},"ruleIndex":0}]}]}"#,
+    )
+    .stdout_capture()
+    .stderr_capture()
+    .unchecked()
+    .run()?;
+  let stderr = String::from_utf8(output.stderr)?;
+  assert!(!output.status.success());
+  assert!(output.stdout.is_empty());
+  assert!(
+    stderr.contains("error: #/runs/0/results/0/message: must have any of")
+  );
+  assert!(
+    stderr.contains("error: #/runs/0/results/0/ruleIndex: refers to rule 0")
+  );
+
+  Ok(())
+}
@@ -0,0 +1,102 @@
+// Writes results as a Checkstyle XML report, which Jenkins' warnings-ng plugin
+// and many other dashboards ingest: results are grouped by file, and every
+// result is an error element of its file
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use serde_sarif::sarif::ResultLevel;
+
+use crate::xml::escape;
+
+// The severity of an error
+pub(crate) fn severity(level: ResultLevel) -> &'static str {
+  match level {
+    ResultLevel::Error => "error",
+    ResultLevel::Warning => "warning",
+    ResultLevel::Note => "info",
+    ResultLevel::None => "ignore",
+  }
+}
+
+// An error of a file, converted from a result
+pub(crate) struct Error {
+  pub(crate) line: Option<i64>,
+  pub(crate) column: Option<i64>,
+  pub(crate) severity: &'static str,
+  pub(crate) message: String,
+  // the rule of the result, or its tool if it has none
+  pub(crate) source: String,
+}
+
+// Writes the errors of every file of `files` as a Checkstyle XML report to
+// `writer`
+pub(crate) fn write_report<W: Write>(
+  mut writer: W,
+  files: &BTreeMap<String, Vec<Error>>,
+) -> io::Result<()> {
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(writer, r#"<checkstyle version="4.3">"#)?;
+  for (name, errors) in files {
+    writeln!(writer, r#"  <file name="{}">"#, escape(name))?;
+    for error in errors {
+      write!(writer, "    <error")?;
+      if let Some(line) = error.line {
+        write!(writer, r#" line="{}""#, line)?;
+      }
+      if let Some(column) = error.column {
+        write!(writer, r#" column="{}""#, column)?;
+      }
+      writeln!(
+        writer,
+        r#" severity="{}" message="{}" source="{}"/>"#,
+        error.severity,
+        escape(&error.message),
+        escape(&error.source)
+      )?;
+    }
+    writeln!(writer, "  </file>")?;
+  }
+  writeln!(writer, "</checkstyle>")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_write_report() {
+    let files = BTreeMap::from([(
+      "src/main.rs".to_string(),
+      vec![
+        Error {
+          line: Some(3),
+          column: Some(5),
+          severity: severity(ResultLevel::Warning),
+          message: "used `unwrap()` on an `Option<&str>` value".into(),
+          source: "clippy::unwrap_used".into(),
+        },
+        Error {
+          line: None,
+          column: None,
+          severity: severity(ResultLevel::Note),
+          message: "crate is missing documentation".into(),
+          source: "clippy".into(),
+        },
+      ],
+    )]);
+    let mut report = vec![];
+    write_report(&mut report, &files).unwrap();
+    assert_eq!(
+      String::from_utf8(report).unwrap(),
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="src/main.rs">
+    <error line="3" column="5" severity="warning" message="used `unwrap()` on an `Option&lt;&amp;str&gt;` value" source="clippy::unwrap_used"/>
+    <error severity="info" message="crate is missing documentation" source="clippy"/>
+  </file>
+</checkstyle>
+"#
+    );
+  }
+}
@@ -1,41 +1,105 @@
-struct ProblemMatchers {
-  problem_matcher: Vec<ProblemMatcher>,
-}
+// Formats results as the workflow commands of GitHub Actions (ex.
+// `::error file=src/main.rs,line=3,col=5::message`), which the runner turns
+// into annotations of the lines of pull request diffs. See
+// https://docs.github.com/en/actions/reference/workflow-commands-for-github-actions
+
+use serde_sarif::sarif::ResultLevel;
+use std::fmt;
 
-struct ProblemMatcher {
-  owner: String,
-  pattern: Vec<ProblemMatcherPattern>,
+// Escapes the message of a workflow command
+fn escape_data(value: &str) -> String {
+  value
+    .replace('%', "%25")
+    .replace('\r', "%0D")
+    .replace('\n', "%0A")
 }
 
-struct ProblemMatcherPattern {
-  regexp: String,
-  severity: i32,
-  file: i32,
-  line: i32,
-  column: i32,
-  message: i32,
+// Escapes the value of a property of a workflow command, which additionally
+// cannot contain the separators of properties
+fn escape_property(value: &str) -> String {
+  escape_data(value).replace(':', "%3A").replace(',', "%2C")
 }
 
-pub fn enable_problem_matcher() {
-  if is_running_in_gha() && atty::is(atty::Stream::Stdout) {
-    let matcher_str = include_str!("problem-matchers/sarif-plain-matcher.json");
+// An annotation of a result, printed as a workflow command
+#[derive(Default)]
+pub(crate) struct Annotation {
+  pub(crate) level: Option<ResultLevel>,
+  pub(crate) file: Option<String>,
+  pub(crate) line: Option<i64>,
+  pub(crate) end_line: Option<i64>,
+  pub(crate) col: Option<i64>,
+  pub(crate) end_column: Option<i64>,
+  pub(crate) title: Option<String>,
+  pub(crate) message: String,
+}
 
-    println!("::add-matcher::sarif-plain-matcher.json")
+impl fmt::Display for Annotation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    // annotations are only created for errors, warnings and notices
+    let command = match self.level {
+      Some(ResultLevel::Error) => "error",
+      Some(ResultLevel::Warning) => "warning",
+      _ => "notice",
+    };
+    let numbers = [
+      ("line", self.line),
+      ("endLine", self.end_line),
+      ("col", self.col),
+      ("endColumn", self.end_column),
+    ];
+    let properties: Vec<String> = self
+      .file
+      .iter()
+      .map(|file| format!("file={}", escape_property(file)))
+      .chain(numbers.iter().filter_map(|(name, value)| {
+        value.map(|value| format!("{}={}", name, value))
+      }))
+      .chain(
+        self
+          .title
+          .iter()
+          .map(|title| format!("title={}", escape_property(title))),
+      )
+      .collect();
+    write!(f, "::{}", command)?;
+    if !properties.is_empty() {
+      write!(f, " {}", properties.join(","))?;
+    }
+    write!(f, "::{}", escape_data(&self.message))
   }
 }
 
-pub fn disable_problem_matcher() {
-  if is_running_in_gha() && atty::is(atty::Stream::Stdout) {
-    println!("::remove-matcher owner=sarif-plain::")
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_escape() {
+    assert_eq!(escape_data("100%\r\na: b, c"), "100%25%0D%0Aa: b, c");
+    assert_eq!(escape_property("clippy::a,b%"), "clippy%3A%3Aa%2Cb%25");
   }
-}
 
-fn is_running_in_gha() -> bool {
-  match std::env::var("GITHUB_ACTIONS")
-    .unwrap_or("false".to_string())
-    .as_str()
-  {
-    "true" => true,
-    _ => false,
+  #[test]
+  fn test_annotation() {
+    let annotation = Annotation {
+      level: Some(ResultLevel::Warning),
+      file: Some("src/main.rs".into()),
+      line: Some(3),
+      end_line: Some(3),
+      col: Some(5),
+      end_column: Some(12),
+      title: Some("clippy::unwrap_used".into()),
+      message: "used `unwrap()`\non an `Option` value".into(),
+    };
+    assert_eq!(
+      annotation.to_string(),
+      "::warning file=src/main.rs,line=3,endLine=3,col=5,endColumn=12,\
+       title=clippy%3A%3Aunwrap_used::used `unwrap()`%0Aon an `Option` value"
+    );
+    let annotation = Annotation {
+      message: "no location".into(),
+      ..Default::default()
+    };
+    assert_eq!(annotation.to_string(), "::notice::no location");
   }
 }
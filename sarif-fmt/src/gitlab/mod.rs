@@ -0,0 +1,77 @@
+// Formats results as the issues of a GitLab Code Quality report, which merge
+// requests show in their widget and diff. See
+// https://docs.gitlab.com/ee/ci/testing/code_quality.html#code-quality-report-format
+
+use serde::Serialize;
+use serde_sarif::sarif::ResultLevel;
+
+// The severity of an issue
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Severity {
+  Info,
+  Minor,
+  Major,
+}
+
+impl From<ResultLevel> for Severity {
+  fn from(level: ResultLevel) -> Self {
+    match level {
+      ResultLevel::Error => Severity::Major,
+      ResultLevel::Warning => Severity::Minor,
+      ResultLevel::Note | ResultLevel::None => Severity::Info,
+    }
+  }
+}
+
+// The first line of the location of an issue
+#[derive(Serialize, Debug)]
+pub(crate) struct Lines {
+  pub(crate) begin: i64,
+}
+
+// The location of an issue, relative to the root of the repository
+#[derive(Serialize, Debug)]
+pub(crate) struct Location {
+  pub(crate) path: String,
+  pub(crate) lines: Lines,
+}
+
+// An issue of a Code Quality report, converted from a result
+#[derive(Serialize, Debug)]
+pub(crate) struct Issue {
+  pub(crate) description: String,
+  pub(crate) check_name: String,
+  pub(crate) fingerprint: String,
+  pub(crate) severity: Severity,
+  pub(crate) location: Location,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_issue() {
+    let issue = Issue {
+      description: "used `unwrap()` on an `Option` value".into(),
+      check_name: "clippy::unwrap_used".into(),
+      fingerprint: "7815696ecbf1c96e6894b779456d330e".into(),
+      severity: ResultLevel::Warning.into(),
+      location: Location {
+        path: "src/main.rs".into(),
+        lines: Lines { begin: 3 },
+      },
+    };
+    assert_eq!(
+      serde_json::to_value(&issue).unwrap(),
+      serde_json::json!({
+        "description": "used `unwrap()` on an `Option` value",
+        "check_name": "clippy::unwrap_used",
+        "fingerprint": "7815696ecbf1c96e6894b779456d330e",
+        "severity": "minor",
+        "location": { "path": "src/main.rs", "lines": { "begin": 3 } }
+      })
+    );
+  }
+}
@@ -0,0 +1,131 @@
+// Writes results as a JUnit XML report, which most CI systems render as test
+// results: every run is a test suite named after its tool, every rule a test
+// case, and every result of a rule a failure of its test case
+
+use std::io::{self, Write};
+
+use crate::xml::escape;
+
+// A failure of a test case, converted from a result
+pub(crate) struct Failure {
+  pub(crate) message: String,
+  // the level of the result
+  pub(crate) kind: String,
+  // the location and message of the result (ex. "src/main.rs:3: message")
+  pub(crate) text: String,
+}
+
+// The test case of a rule, which fails if the rule has results
+pub(crate) struct TestCase {
+  pub(crate) name: String,
+  pub(crate) failures: Vec<Failure>,
+}
+
+// The test suite of a run
+pub(crate) struct TestSuite {
+  pub(crate) name: String,
+  pub(crate) test_cases: Vec<TestCase>,
+}
+
+impl TestSuite {
+  fn failures(&self) -> usize {
+    self
+      .test_cases
+      .iter()
+      .filter(|test_case| !test_case.failures.is_empty())
+      .count()
+  }
+}
+
+// Writes `test_suites` as a JUnit XML report to `writer`
+pub(crate) fn write_report<W: Write>(
+  mut writer: W,
+  test_suites: &[TestSuite],
+) -> io::Result<()> {
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(
+    writer,
+    r#"<testsuites tests="{}" failures="{}">"#,
+    test_suites
+      .iter()
+      .map(|test_suite| test_suite.test_cases.len())
+      .sum::<usize>(),
+    test_suites.iter().map(TestSuite::failures).sum::<usize>()
+  )?;
+  for test_suite in test_suites {
+    writeln!(
+      writer,
+      r#"  <testsuite name="{}" tests="{}" failures="{}">"#,
+      escape(&test_suite.name),
+      test_suite.test_cases.len(),
+      test_suite.failures()
+    )?;
+    for test_case in &test_suite.test_cases {
+      write!(
+        writer,
+        r#"    <testcase name="{}" classname="{}""#,
+        escape(&test_case.name),
+        escape(&test_suite.name)
+      )?;
+      if test_case.failures.is_empty() {
+        writeln!(writer, "/>")?;
+        continue;
+      }
+      writeln!(writer, ">")?;
+      for failure in &test_case.failures {
+        writeln!(
+          writer,
+          r#"      <failure message="{}" type="{}">{}</failure>"#,
+          escape(&failure.message),
+          escape(&failure.kind),
+          escape(&failure.text)
+        )?;
+      }
+      writeln!(writer, "    </testcase>")?;
+    }
+    writeln!(writer, "  </testsuite>")?;
+  }
+  writeln!(writer, "</testsuites>")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_write_report() {
+    let test_suites = vec![TestSuite {
+      name: "clippy".into(),
+      test_cases: vec![
+        TestCase {
+          name: "clippy::needless_if".into(),
+          failures: vec![],
+        },
+        TestCase {
+          name: "clippy::unwrap_used".into(),
+          failures: vec![Failure {
+            message: "used `unwrap()` on an `Option<&str>` value".into(),
+            kind: "warning".into(),
+            text: "src/main.rs:3: used `unwrap()` on an `Option<&str>` value"
+              .into(),
+          }],
+        },
+      ],
+    }];
+    let mut report = vec![];
+    write_report(&mut report, &test_suites).unwrap();
+    assert_eq!(
+      String::from_utf8(report).unwrap(),
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="2" failures="1">
+  <testsuite name="clippy" tests="2" failures="1">
+    <testcase name="clippy::needless_if" classname="clippy"/>
+    <testcase name="clippy::unwrap_used" classname="clippy">
+      <failure message="used `unwrap()` on an `Option&lt;&amp;str&gt;` value" type="warning">src/main.rs:3: used `unwrap()` on an `Option&lt;&amp;str&gt;` value</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+    );
+  }
+}
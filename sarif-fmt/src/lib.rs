@@ -0,0 +1,1833 @@
+#![doc(html_root_url = "https://docs.rs/sarif-fmt/0.8.0")]
+#![recursion_limit = "256"]
+//! This crate provides a command line tool to pretty print SARIF files to
+//! easy human readable output.
+//!
+//! The latest [documentation can be found here](https://docs.rs/sarif_fmt).
+//!
+//! SARIF or the Static Analysis Results Interchange Format is an industry
+//! standard format for the output of static analysis tools. More information
+//! can be found on the official website: [https://sarifweb.azurewebsites.net/](https://sarifweb.azurewebsites.net/).
+//!
+//! ## Installation
+//!
+//! `sarif-fmt` may be installed via `cargo`
+//!
+//! ```shell
+//! cargo install sarif-fmt
+//! ```
+//!
+//! or downloaded directly from Github Releases
+//!
+//!```shell
+//! # make sure to adjust the target and version (you may also want to pin to a specific version)
+//! curl -sSL https://github.com/psastras/sarif-rs/releases/download/sarif-fmt-latest/sarif-fmt-x86_64-unknown-linux-gnu -o sarif-fmt
+//! ```
+//!
+//! ## Usage
+//!
+//! For most cases, simply pipe a SARIF file into `sarif-fmt` (`cat ./foo.sarif | sarif-fmt`)
+//!
+//! ## Example
+//!
+//!```shell
+//! $ cargo clippy --message-format=json | clippy-sarif | sarif-fmt
+//! $ warning: using `Option.and_then(|x| Some(y))`, which is more succinctly expressed as `map(|x| y)`
+//!     ┌─ sarif-fmt/src/bin.rs:423:13
+//!     │
+//! 423 │ ╭             the_rule
+//! 424 │ │               .full_description
+//! 425 │ │               .as_ref()
+//! 426 │ │               .and_then(|mfms| Some(mfms.text.clone()))
+//!     │ ╰───────────────────────────────────────────────────────^
+//!     │
+//!     = `#[warn(clippy::bind_instead_of_map)]` on by default
+//!       for further information visit https://rust-lang.github.io/rust-clippy/master#bind_instead_of_map
+//! ```
+//!
+//! Often it is useful to record the SARIF file for machine processing but also
+//! print the nicely formatted results to stdout at the same time. This can be done
+//! using the `tee` command:
+//!
+//! ```shell
+//! $ clang-tidy -checks=cert-* cpp.cpp -- | clang-tidy-sarif | tee clang-tidy.sarif | sarif-fmt
+//! $ 2 warnings generated.
+//! warning: 'atoi' used to convert a string to an integer value, but function will not report conversion errors; consider using 'strtol' instead [cert-err34-c]
+//!   ┌─ /home/psastras/repos/sarif-rs/sarif-fmt/tests/data/cpp.cpp:4:10
+//!   │
+//! 4 │   return atoi(num);
+//!   │          ^^^^^^^^^^
+//!
+//! warning: calling 'system' uses a command processor [cert-env33-c]
+//!   ┌─ /home/psastras/repos/sarif-rs/sarif-fmt/tests/data/cpp.cpp:8:3
+//!   │
+//! 8 │   system("ls");
+//!   │   ^^^^^^^^^^^^^
+//!
+//! $ cat clang-tidy.sarif
+//! {
+//!   "runs": [
+//!     {
+//!       "results": [
+//!         {
+//!           "level": "warning",
+//!           "locations": [
+//!             {
+//!               "physicalLocation": {
+//!                 "artifactLocation": {
+//!                   "uri": "cpp.cpp"
+//!                 },
+//!                 "region": {
+//!                   "startColumn": 10,
+//!                   "startLine": 4
+//!                 }
+//!               }
+//!             }
+//!           ],
+//!           "message": {
+//!             "text": "'atoi' used to convert a string to an integer value, but function will not report conversion errors; consider using 'strtol' instead [cert-err34-c]"
+//!           }
+//!         },
+//!         {
+//!           "level": "warning",
+//!           "locations": [
+//!             {
+//!               "physicalLocation": {
+//!                 "artifactLocation": {
+//!                   "uri": "cpp.cpp"
+//!                 },
+//!                 "region": {
+//!                   "startColumn": 3,
+//!                   "startLine": 8
+//!                 }
+//!               }
+//!             }
+//!           ],
+//!           "message": {
+//!             "text": "calling 'system' uses a command processor [cert-env33-c]"
+//!           }
+//!         }
+//!       ],
+//!       "tool": {
+//!         "driver": {
+//!           "name": "clang-tidy"
+//!         }
+//!       }
+//!     }
+//!   ],
+//!   "version": "2.1.0"
+//! }
+//! ```
+//!
+//! `sarif-fmt --validate` checks the input against the SARIF 2.1.0 schema and
+//! specification before printing it, and fails with a JSON pointer to every
+//! offending node instead:
+//!
+//! ```shell
+//! $ sarif-fmt --validate -i results.sarif
+//! error: #/runs/0/results/3/ruleIndex: refers to rule 12 but /runs/0/tool/driver/rules has 4
+//! error: #/runs/0/results/5/locations/0/physicalLocation/region/endLine: must be greater than or equal to startLine (8)
+//! Error: input is not a valid SARIF log (2 violations)
+//! ```
+//!
+//! Relative artifact URIs are resolved against their `uriBaseId`, as defined in
+//! the `originalUriBaseIds` of the run (recursively, when a base is itself
+//! relative to another). When the log was produced in another directory, the
+//! base can be overridden with `--src-root NAME=PATH`, which may be repeated:
+//!
+//! ```shell
+//! $ sarif-fmt --src-root %SRCROOT%=. -i results.sarif
+//! ```
+//!
+//! Results whose files are not on disk (ex. when the log was produced on another
+//! machine) are rendered from the contents embedded in the log instead: the
+//! `contents` of their artifact in `run.artifacts` (as text or base64 encoded
+//! binary), or else the `snippet` of their `contextRegion` or `region`.
+//!
+//! The `codeFlows` of a result (ex. the path tainted data takes through a program,
+//! as reported by CodeQL) are rendered after it as a numbered trace of their
+//! steps, indented by their `nestingLevel`. Essential steps are highlighted like
+//! the result itself, and `--collapse-unimportant` leaves out the steps marked as
+//! unimportant.
+//!
+//! The `stacks` of a result (ex. the backtrace of undefined behavior reported by
+//! miri, or of a memory error reported by a sanitizer) are rendered after it as
+//! a numbered list of their frames, innermost first, with the source line of
+//! every frame that has a location.
+//!
+//! A large log (ex. merged from several tools) can be narrowed down to the
+//! results that matter with filters, which are combined: `--level LEVEL` keeps
+//! the results of at least that level (as resolved from the result, its rule and
+//! the run), `--rule RULE` and `--exclude-rule RULE` keep or leave out the
+//! results of a rule, `--path GLOB` keeps the results located in matching files
+//! (relative to the current directory, once their `uriBaseId` is resolved),
+//! `--tool TOOL` keeps the results of a tool, and `--kind KIND` keeps the results
+//! of a kind. All of them but `--level` may be repeated:
+//!
+//! ```shell
+//! $ sarif-fmt --level warning --tool clippy --path 'src/**' -i results.sarif
+//! ```
+//!
+//! `sarif-fmt` exits with a zero status after printing the results, unless
+//! `--fail-on LEVEL` is given and a printed result has that level or a more
+//! severe one (as resolved from the result, its rule and the run), or
+//! `--max-warnings N` is given and more than N warnings are printed. This makes it
+//! usable as the single gate step of a CI pipeline after the converters run:
+//!
+//! ```shell
+//! $ cargo clippy --message-format=json | clippy-sarif | sarif-fmt --fail-on error --max-warnings 10
+//! ```
+//!
+//! In GitHub Actions, `--message-format github` prints every result as a workflow
+//! command (ex. `::error file=src/main.rs,line=3,col=5,title=RULE::message`), which
+//! annotates its line in the diff of pull requests without uploading the log to
+//! code scanning:
+//!
+//! ```shell
+//! $ sarif-fmt --message-format github --fail-on error -i results.sarif
+//! ```
+//!
+//! In GitLab CI, `--message-format gitlab` exports the results as a Code Quality
+//! report, which merge requests show in their widget. Every result becomes an
+//! issue with its message, rule, severity (`major` for errors, `minor` for
+//! warnings and `info` otherwise) and the first line of its location, and with
+//! its fingerprint from the log, or else one hashed from these:
+//!
+//! ```yaml
+//! code_quality:
+//!   script:
+//!     - cargo clippy --message-format=json | clippy-sarif | sarif-fmt --message-format gitlab > gl-code-quality-report.json
+//!   artifacts:
+//!     reports:
+//!       codequality: gl-code-quality-report.json
+//! ```
+//!
+//! For CI systems which only render test reports, `--message-format junit`
+//! exports the results as JUnit XML: every run is a `<testsuite>` named after its
+//! tool, every rule a `<testcase>`, and every result of a rule (other than those
+//! of level `none`) a `<failure>` of its test case, with its location and message.
+//! The rules of the tool without results are passing test cases:
+//!
+//! ```shell
+//! $ sarif-fmt --message-format junit -i results.sarif > junit.xml
+//! ```
+//!
+//! For dashboards which ingest Checkstyle XML (ex. the warnings-ng plugin of
+//! Jenkins), `--message-format checkstyle` groups the results by file, as
+//! `<error>` elements with their line, column, severity, message and rule as
+//! `source`:
+//!
+//! ```shell
+//! $ shellcheck -f json scripts/*.sh | shellcheck-sarif | sarif-fmt --message-format checkstyle > checkstyle.xml
+//! ```
+//!
+mod checkstyle;
+mod github;
+mod gitlab;
+mod junit;
+mod xml;
+
+use anyhow::Result;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use clap::{Parser, ValueEnum};
+use codespan_reporting::diagnostic;
+use codespan_reporting::diagnostic::Diagnostic;
+use codespan_reporting::diagnostic::Label;
+use codespan_reporting::files::Files;
+use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::term;
+use codespan_reporting::term::termcolor::Color;
+use codespan_reporting::term::termcolor::ColorChoice;
+use codespan_reporting::term::termcolor::ColorSpec;
+use codespan_reporting::term::termcolor::StandardStream;
+use codespan_reporting::term::termcolor::WriteColor;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde_sarif::sarif;
+use serde_sarif::sarif::ResultColumnKind;
+use serde_sarif::sarif::ResultKind;
+use serde_sarif::sarif::ResultLevel;
+use serde_sarif::sarif::ThreadFlowLocationImportance;
+use serde_sarif::stream::for_each_result;
+use serde_sarif::visit::rule_id;
+use serde_sarif::{fingerprint, region, uri};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Write;
+use std::io::{Cursor, Read, Seek};
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str::FromStr;
+
+// The SARIF input, which is read twice to stream its results: once for the
+// run level data, which may follow the results, and once for the results.
+// stdin cannot be sought back, so it is read in memory first.
+enum Input {
+  File(File),
+  Buffer(Cursor<Vec<u8>>),
+}
+
+impl Input {
+  fn open(path: Option<&Path>) -> Result<Self> {
+    match path {
+      Some(path) => Ok(Input::File(File::open(path)?)),
+      None => {
+        let mut buffer = vec![];
+        std::io::stdin().lock().read_to_end(&mut buffer)?;
+        Ok(Input::Buffer(Cursor::new(buffer)))
+      }
+    }
+  }
+
+  // Returns the input after checking it against the SARIF schema
+  fn validate(self) -> Result<Self> {
+    match self {
+      Input::File(file) => Ok(Input::File(validate(file)?)),
+      Input::Buffer(buffer) => Ok(Input::Buffer(validate(buffer)?)),
+    }
+  }
+
+  // Calls `f` with every result of the log, and returns the runs of the log
+  // without their results
+  fn for_each_result<F>(self, f: F) -> Result<Vec<sarif::Run>>
+  where
+    F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<()>,
+  {
+    match self {
+      Input::File(file) => for_each_result(file, f),
+      Input::Buffer(buffer) => for_each_result(buffer, f),
+    }
+  }
+}
+
+/// Checks a SARIF log against the SARIF 2.1.0 schema and specification,
+/// printing every violation to stderr and failing if any is found
+///
+/// # Arguments
+///
+/// * `log` - The SARIF log, as parsed JSON
+pub fn validate_log(log: &serde_json::Value) -> Result<()> {
+  let violations = serde_sarif::validate::validate(log);
+  if !violations.is_empty() {
+    violations
+      .iter()
+      .for_each(|violation| eprintln!("error: {}", violation));
+    anyhow::bail!(
+      "input is not a valid SARIF log ({} violations)",
+      violations.len()
+    );
+  }
+  Ok(())
+}
+
+fn validate<R: Read + Seek>(mut input: R) -> Result<R> {
+  let log: serde_json::Value =
+    serde_json::from_reader(std::io::BufReader::new(&mut input))?;
+  validate_log(&log)?;
+  input.rewind()?;
+  Ok(input)
+}
+
+// Returns the originalUriBaseIds of `run`, with the bases given on the command
+// line taking precedence over those of the log
+fn uri_base_ids(
+  run: &sarif::Run,
+  src_roots: &[(String, PathBuf)],
+) -> BTreeMap<String, sarif::ArtifactLocation> {
+  let mut uri_base_ids = run.original_uri_base_ids.clone().unwrap_or_default();
+  src_roots.iter().for_each(|(name, path)| {
+    let mut base = uri::from_path(path);
+    if !base.ends_with('/') {
+      base.push('/');
+    }
+    uri_base_ids.insert(
+      name.clone(),
+      sarif::ArtifactLocation::builder().uri(base).build(),
+    );
+  });
+  uri_base_ids
+}
+
+// Parses a `NAME=PATH` base given on the command line
+fn parse_src_root(value: &str) -> Result<(String, PathBuf)> {
+  match value.split_once('=') {
+    Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+      Ok((name.to_string(), PathBuf::from(path)))
+    }
+    _ => anyhow::bail!("expected NAME=PATH, found {}", value),
+  }
+}
+
+fn try_find_file(
+  physical_location: &sarif::PhysicalLocation,
+  uri_base_ids: &BTreeMap<String, sarif::ArtifactLocation>,
+) -> Result<PathBuf> {
+  let artifact_location = physical_location
+    .artifact_location
+    .as_ref()
+    .map_or_else(|| Err(anyhow::anyhow!("No artifact location.")), Ok)?;
+  let path = uri::resolve_path(artifact_location, Some(uri_base_ids))?;
+  if path.exists() {
+    return Ok(path);
+  }
+
+  // the base may not be where the log was produced, check if the uri is
+  // relative to the current directory by chance
+  let uri = artifact_location
+    .uri
+    .as_deref()
+    .map_or_else(|| Err(anyhow::anyhow!("No uri.")), Ok)?;
+  let path = uri::to_path(uri);
+  if path.exists() {
+    Ok(path)
+  } else {
+    Err(anyhow::anyhow!("Path not found: {:#?}", path))
+  }
+}
+
+// The origin of the contents of a file, which are read or decoded once and
+// shared by every location referring to it
+#[derive(PartialEq, Eq, Hash)]
+enum SourceKey {
+  File(PathBuf),
+  // the index of a run, and of an artifact in its artifacts
+  Artifact(usize, usize),
+}
+
+// Returns `bytes` decoded as text in `encoding`, which defaults to utf-8
+fn decode(bytes: &[u8], encoding: Option<&str>) -> Option<String> {
+  let utf16 = |bytes: &[u8], bom: [u8; 2], from: fn([u8; 2]) -> u16| {
+    let (units, _) = bytes.strip_prefix(&bom).unwrap_or(bytes).as_chunks();
+    let units: Vec<u16> = units.iter().map(|&unit| from(unit)).collect();
+    String::from_utf16(&units).ok()
+  };
+  match encoding.map(str::to_ascii_lowercase).as_deref() {
+    Some("utf-16le") => utf16(bytes, [0xff, 0xfe], u16::from_le_bytes),
+    Some("utf-16be") => utf16(bytes, [0xfe, 0xff], u16::from_be_bytes),
+    _ => String::from_utf8(bytes.to_vec()).ok(),
+  }
+}
+
+// Returns the text of `content`, decoding its base64 binary contents if it
+// has no text
+fn content_text(
+  content: &sarif::ArtifactContent,
+  encoding: Option<&str>,
+) -> Option<String> {
+  match (content.text.as_ref(), content.binary.as_ref()) {
+    (Some(text), _) => Some(text.clone()),
+    (None, Some(binary)) => {
+      decode(&BASE64_STANDARD.decode(binary).ok()?, encoding)
+    }
+    (None, None) => None,
+  }
+}
+
+// Returns the artifact of `artifact_location` in `run.artifacts` and its
+// index, found by index or else by uri
+fn find_artifact<'a>(
+  run: &'a sarif::Run,
+  artifact_location: &sarif::ArtifactLocation,
+) -> Option<(usize, &'a sarif::Artifact)> {
+  let artifacts = run.artifacts.as_ref()?;
+  match artifact_location.index {
+    Some(index) => Some((index as usize, artifacts.get(index as usize)?)),
+    None => artifacts.iter().enumerate().find(|(_, artifact)| {
+      artifact.location.as_ref().is_some_and(|location| {
+        artifact_location.uri.is_some()
+          && location.uri == artifact_location.uri
+          && location.uri_base_id == artifact_location.uri_base_id
+      })
+    }),
+  }
+}
+
+// Returns `snippet` placed at the start of `region`, preceded by empty lines
+// and spaces so that the lines and columns of the file still apply to it
+fn snippet_source(
+  region: &sarif::Region,
+  encoding: Option<&str>,
+) -> Option<String> {
+  let mut text = content_text(region.snippet.as_ref()?, encoding)?;
+  // so that a region ending with the snippet ends at the end of a line
+  if !text.ends_with('\n') {
+    text.push('\n');
+  }
+  let start_line = region.start_line?.max(1) as usize;
+  let start_column = region.start_column.unwrap_or(1).max(1) as usize;
+  Some(format!(
+    "{}{}{}",
+    "\n".repeat(start_line - 1),
+    " ".repeat(start_column - 1),
+    text
+  ))
+}
+
+// Adds the file of `physical_location` to `files` and returns its id. Every
+// location gets its own id, so that each is rendered with its own header, but
+// the contents of a file are only read the first time it is referenced. Files
+// which are not on disk fall back to the contents embedded in the log: those
+// of their artifact, or else the snippet of the context region or of the
+// region of the location, in which case `true` is returned with the id.
+fn get_file_id(
+  files: &mut SimpleFiles<String, Rc<str>>,
+  contents: &mut HashMap<SourceKey, Rc<str>>,
+  physical_location: &sarif::PhysicalLocation,
+  run_index: usize,
+  run: &sarif::Run,
+  uri_base_ids: &BTreeMap<String, sarif::ArtifactLocation>,
+) -> Option<(usize, bool)> {
+  let artifact_location = physical_location.artifact_location.as_ref()?;
+  let artifact = find_artifact(run, artifact_location);
+  let uri = artifact_location
+    .uri
+    .clone()
+    .or_else(|| artifact?.1.location.as_ref()?.uri.clone())?;
+  let encoding = artifact
+    .and_then(|(_, artifact)| artifact.encoding.as_deref())
+    .or(run.default_encoding.as_deref());
+
+  let mut cached = |key: SourceKey, read: &dyn Fn() -> Option<String>| {
+    if let Some(source) = contents.get(&key) {
+      return Some(source.clone());
+    }
+    let source: Rc<str> = read()?.into();
+    contents.insert(key, source.clone());
+    Some(source)
+  };
+  let source = try_find_file(physical_location, uri_base_ids)
+    .ok()
+    .and_then(|path| {
+      cached(SourceKey::File(path.clone()), &|| {
+        std::fs::read_to_string(&path).ok()
+      })
+    })
+    .or_else(|| {
+      let (index, artifact) = artifact?;
+      cached(SourceKey::Artifact(run_index, index), &|| {
+        content_text(artifact.contents.as_ref()?, encoding)
+      })
+    })
+    .map(|source| (source, false))
+    .or_else(|| {
+      physical_location
+        .context_region
+        .as_ref()
+        .and_then(|region| snippet_source(region, encoding))
+        .or_else(|| {
+          snippet_source(physical_location.region.as_ref()?, encoding)
+        })
+        .map(|source| (Rc::from(source), true))
+    })?;
+  Some((files.add(uri, source.0), source.1))
+}
+
+// Returns the byte range of `region` in the file `file_id`. The offsets of a
+// region refer to the whole file, so only the lines and columns of the region
+// of a `snippet` are used, and a region ending outside of the file is reduced
+// to its start.
+fn get_byte_range(
+  file_id: usize,
+  files: &SimpleFiles<String, Rc<str>>,
+  region: &sarif::Region,
+  column_kind: ResultColumnKind,
+  snippet: bool,
+) -> Option<Range<usize>> {
+  let source = files.source(file_id).ok()?;
+  let mut region = region.clone();
+  if snippet {
+    region.byte_offset = None;
+    region.char_offset = None;
+  }
+  region::byte_range(source, &region, column_kind).or_else(|| {
+    region.byte_length = Some(0);
+    region.char_length = Some(0);
+    region.end_line = region.start_line;
+    region.end_column = Some(region.start_column.unwrap_or(1));
+    region::byte_range(source, &region, column_kind)
+  })
+}
+
+// Adds the file of `physical_location` to `files`, and returns its id and the
+// byte range of the region of the location in it
+fn get_label(
+  files: &mut SimpleFiles<String, Rc<str>>,
+  contents: &mut HashMap<SourceKey, Rc<str>>,
+  physical_location: &sarif::PhysicalLocation,
+  run_index: usize,
+  run: &sarif::Run,
+  uri_base_ids: &BTreeMap<String, sarif::ArtifactLocation>,
+) -> Option<(usize, Range<usize>)> {
+  let region = physical_location.region.as_ref()?;
+  let (file_id, snippet) = get_file_id(
+    files,
+    contents,
+    physical_location,
+    run_index,
+    run,
+    uri_base_ids,
+  )?;
+  let range =
+    get_byte_range(file_id, files, region, region::column_kind(run), snippet)?;
+  Some((file_id, range))
+}
+
+// Returns the importance of a step of a thread flow, which defaults to
+// "important"
+fn resolve_importance(
+  thread_flow_location: &sarif::ThreadFlowLocation,
+  shared: Option<&sarif::ThreadFlowLocation>,
+) -> ThreadFlowLocationImportance {
+  thread_flow_location
+    .importance
+    .as_ref()
+    .or_else(|| shared?.importance.as_ref())
+    .and_then(|importance| importance.as_str())
+    .and_then(|importance| {
+      ThreadFlowLocationImportance::from_str(importance).ok()
+    })
+    .unwrap_or(ThreadFlowLocationImportance::Important)
+}
+
+// Returns a note for every thread flow of the code flows of `result`, with a
+// numbered label for each of its steps, indented by their nesting level.
+// Essential steps are primary labels, and unimportant steps are left out when
+// `collapse_unimportant` is set.
+fn resolve_code_flows(
+  files: &mut SimpleFiles<String, Rc<str>>,
+  contents: &mut HashMap<SourceKey, Rc<str>>,
+  run_index: usize,
+  run: &sarif::Run,
+  uri_base_ids: &BTreeMap<String, sarif::ArtifactLocation>,
+  result: &sarif::Result,
+  collapse_unimportant: bool,
+) -> Vec<Diagnostic<usize>> {
+  let mut diagnostics = vec![];
+  result.code_flows.iter().flatten().enumerate().for_each(
+    |(code_flow_index, code_flow)| {
+      let thread_flows = code_flow.thread_flows.len();
+      code_flow.thread_flows.iter().enumerate().for_each(
+        |(thread_flow_index, thread_flow)| {
+          let mut title = format!("code flow {}", code_flow_index + 1);
+          if thread_flows > 1 {
+            title += &format!(", thread flow {}", thread_flow_index + 1);
+          }
+          if let Some(text) = thread_flow
+            .message
+            .as_ref()
+            .or(code_flow.message.as_ref())
+            .and_then(|message| message.text.as_ref())
+          {
+            title += &format!(": {}", text);
+          }
+          let mut diagnostic =
+            Diagnostic::new(diagnostic::Severity::Note).with_message(title);
+          let mut collapsed = 0;
+          thread_flow.locations.iter().enumerate().for_each(
+            |(step, thread_flow_location)| {
+              // the properties of a step may be shared in
+              // run.threadFlowLocations
+              let shared = thread_flow_location.index.and_then(|index| {
+                run.thread_flow_locations.as_ref()?.get(index as usize)
+              });
+              let importance = resolve_importance(thread_flow_location, shared);
+              if collapse_unimportant
+                && importance == ThreadFlowLocationImportance::Unimportant
+              {
+                collapsed += 1;
+                return;
+              }
+              let location = thread_flow_location
+                .location
+                .as_ref()
+                .or_else(|| shared?.location.as_ref());
+              let nesting_level = thread_flow_location
+                .nesting_level
+                .or_else(|| shared?.nesting_level)
+                .unwrap_or(0)
+                .max(0) as usize;
+              let text = location
+                .and_then(|location| location.message.as_ref())
+                .and_then(|message| message.text.as_deref())
+                .unwrap_or_default();
+              let message =
+                format!("{}{}. {}", "  ".repeat(nesting_level), step + 1, text);
+              match location
+                .and_then(|location| location.physical_location.as_ref())
+                .and_then(|physical_location| {
+                  get_label(
+                    files,
+                    contents,
+                    physical_location,
+                    run_index,
+                    run,
+                    uri_base_ids,
+                  )
+                }) {
+                Some((file_id, range)) => {
+                  let label = match importance {
+                    ThreadFlowLocationImportance::Essential => {
+                      Label::primary(file_id, range)
+                    }
+                    _ => Label::secondary(file_id, range),
+                  };
+                  diagnostic.labels.push(label.with_message(message));
+                }
+                None => diagnostic.notes.push(message),
+              }
+            },
+          );
+          if collapsed > 0 {
+            diagnostic
+              .notes
+              .push(format!("{} unimportant steps collapsed", collapsed));
+          }
+          diagnostics.push(diagnostic);
+        },
+      );
+    },
+  );
+  diagnostics
+}
+
+// Returns a note for every stack of `result`, with a numbered label for each
+// of its frames, innermost first. The innermost frame is the primary label,
+// and frames without a location in a file are listed as notes.
+fn resolve_stacks(
+  files: &mut SimpleFiles<String, Rc<str>>,
+  contents: &mut HashMap<SourceKey, Rc<str>>,
+  run_index: usize,
+  run: &sarif::Run,
+  uri_base_ids: &BTreeMap<String, sarif::ArtifactLocation>,
+  result: &sarif::Result,
+) -> Vec<Diagnostic<usize>> {
+  let mut diagnostics = vec![];
+  result.stacks.iter().flatten().enumerate().for_each(
+    |(stack_index, stack)| {
+      let mut title = format!("stack {}", stack_index + 1);
+      if let Some(text) = stack
+        .message
+        .as_ref()
+        .and_then(|message| message.text.as_ref())
+      {
+        title += &format!(": {}", text);
+      }
+      let mut diagnostic =
+        Diagnostic::new(diagnostic::Severity::Note).with_message(title);
+      stack.frames.iter().enumerate().for_each(|(index, frame)| {
+        let location = frame.location.as_ref();
+        let mut message = format!(
+          "{}. {}",
+          index + 1,
+          location
+            .and_then(|location| location.message.as_ref())
+            .and_then(|message| message.text.as_deref())
+            .unwrap_or("<unknown>")
+        );
+        if let Some(module) = frame.module.as_ref() {
+          message += &format!(" in {}", module);
+        }
+        match location
+          .and_then(|location| location.physical_location.as_ref())
+          .and_then(|physical_location| {
+            get_label(
+              files,
+              contents,
+              physical_location,
+              run_index,
+              run,
+              uri_base_ids,
+            )
+          }) {
+          Some((file_id, range)) => {
+            let label = if index == 0 {
+              Label::primary(file_id, range)
+            } else {
+              Label::secondary(file_id, range)
+            };
+            diagnostic.labels.push(label.with_message(message));
+          }
+          None => diagnostic.notes.push(message),
+        }
+      });
+      diagnostics.push(diagnostic);
+    },
+  );
+  diagnostics
+}
+
+// If kind (§3.27.9) has any value other than "fail", then if level is absent, it SHALL default to "none", and if it is present, it SHALL have the value "none".
+// If kind has the value "fail" and level is absent, then level SHALL be determined by the following procedure:
+// IF rule (§3.27.7) is present THEN
+//     LET theDescriptor be the reportingDescriptor object (§3.49) that it specifies.
+//     # Is there a configuration override for the level property?
+//     IF result.provenance.invocationIndex (§3.27.29, §3.48.6) is >= 0 THEN
+//         LET theInvocation be the invocation object (§3.20) that it specifies.
+//         IF theInvocation.ruleConfigurationOverrides (§3.20.5) is present
+//               AND it contains a configurationOverride object (§3.51) whose
+//               descriptor property (§3.51.2) specifies theDescriptor THEN
+//             LET theOverride be that configurationOverride object.
+//             IF theOverride.configuration.level (§3.51.3, §3.50.3) is present THEN
+//               Set level to theConfiguration.level.
+//     ELSE
+//         # There is no configuration override for level. Is there a default configuration for it?
+//         IF theDescriptor.defaultConfiguration.level (§3.49.14, §, §3.50.3) is present THEN
+//           SET level to theDescriptor.defaultConfiguration.level.
+// IF level has not yet been set THEN
+//     SET level to "warning".
+fn resolve_level(
+  rules: &[sarif::ReportingDescriptor],
+  run: &sarif::Run,
+  result: &sarif::Result,
+) -> sarif::ResultLevel {
+  let kind = result
+    .kind
+    .as_ref()
+    // 3.27.9 kind property
+    // If kind is absent, it SHALL default to "fail".
+    .unwrap_or(&ResultKind::Fail);
+
+  match kind {
+    // If kind has the value "fail" and level is absent, then level SHALL be determined by the following procedure:
+    ResultKind::Fail => match result.level.as_ref() {
+      Some(level) => Some(*level),
+      None => result.rule.as_ref().and_then(|rule| {
+        // IF rule (§3.27.7) is present THEN
+        rule.index.and_then(|rule_index| {
+          rules
+            .get(rule_index as usize)
+            //     LET theDescriptor be the reportingDescriptor object (§3.49) that it specifies.
+            //     # Is there a configuration override for the level property?
+            .and_then(|the_descriptor| {
+              //     IF result.provenance.invocationIndex (§3.27.29, §3.48.6) is >= 0 THEN
+              result
+                .provenance
+                .as_ref()
+                .and_then(|provenance| {
+                  provenance.invocation_index.and_then(|invocation_index| {
+                    run
+                      .invocations
+                      .iter()
+                      .flatten()
+                      .collect::<Vec<_>>()
+                      .get(invocation_index as usize)
+                      // LET theInvocation be the invocation object (§3.20) that it specifies.
+                      // IF theInvocation.ruleConfigurationOverrides (§3.20.5) is present
+                      //       AND it contains a configurationOverride object (§3.51) whose
+                      //       descriptor property (§3.51.2) specifies theDescriptor THEN
+                      .and_then(|the_invocation| {
+                        the_invocation
+                          .rule_configuration_overrides
+                          .as_ref()
+                          .and_then(|rule_configuration_overrides| {
+                            rule_configuration_overrides
+                              .iter()
+                              .find(|v| {
+                                v.descriptor.id.as_ref()
+                                  == Some(&the_descriptor.id)
+                              })
+                              .and_then(|the_override| {
+                                the_override
+                                  .configuration
+                                  .level
+                                  .as_ref()
+                                  .and_then(|value| {
+                                    value.as_str().and_then(|level| {
+                                      sarif::ResultLevel::from_str(level)
+                                        .map_or(None, Option::from)
+                                    })
+                                  })
+                              })
+                          })
+                      })
+                  })
+                })
+                .or_else(|| {
+                  //         # There is no configuration override for level. Is there a default configuration for it?
+                  //         IF theDescriptor.defaultConfiguration.level (§3.49.14, §, §3.50.3) is present THEN
+                  //           SET level to theDescriptor.defaultConfiguration.level.
+                  the_descriptor.default_configuration.as_ref().and_then(
+                    |default_configuration| {
+                      default_configuration.level.as_ref().and_then(|value| {
+                        value.as_str().and_then(|level| {
+                          sarif::ResultLevel::from_str(level)
+                            .map_or(None, Option::from)
+                        })
+                      })
+                    },
+                  )
+                })
+            })
+        })
+      }),
+    },
+    // If kind (§3.27.9) has any value other than "fail", then if level is absent, it SHALL default to "none", and if it is present, it SHALL have the value "none".
+    _ => Some(sarif::ResultLevel::None),
+  }
+  // IF level has not yet been set THEN
+  //     SET level to "warning".
+  .unwrap_or(sarif::ResultLevel::Warning)
+}
+
+// IF theMessage.text is present and the desired language is theRun.language THEN
+//     Use the text or markdown property of theMessage as appropriate.
+// IF the string has not yet been found THEN
+//     IF theMessage occurs as the value of result.message (§3.27.11) THEN
+//         LET theRule be the reportingDescriptor object (§3.49), an element of theComponent.rules (§3.19.23), which defines the rule that was violated by this result.
+//         IF theRule exists AND theRule.messageStrings (§3.49.11) is present AND contains a property whose name equals theMessage.id THEN
+//             LET theMFMS be the multiformatMessageString object (§3.12) that is the value of that property.
+//             Use the text or markdown property of theMFMS as appropriate.
+//     ELSE IF theMessage occurs as the value of notification.message (§3.58.5) THEN
+//         LET theDescriptor be the reportingDescriptor object (§3.49), an element of theComponent.notifications (§3.19.23), which describes this notification.
+//         IF theDescriptor exists AND theDescriptor.messageStrings is present AND contains a property whose name equals theMessage.id THEN
+//             LET theMFMS be the multiformatMessageString object that is the value of that property.
+//             Use the text or markdown property of theMFMS as appropriate.
+// IF the string has not yet been found THEN
+//     IF theComponent.globalMessageStrings (§3.19.22) is present AND contains a property whose name equals theMessage.id THEN
+//             LET theMFMS be the multiformatMessageString object that is the value of that property.
+//             Use the text or markdown property of theMFMS as appropriate.
+// IF the string has not yet been found THEN
+//     The lookup procedure fails (which means the SARIF log file is invalid).
+fn resolve_message_text_from_result(
+  result: &sarif::Result,
+  run: &sarif::Run,
+) -> Option<String> {
+  result
+    .message
+    .text
+    .as_ref()
+    .cloned()
+    // IF the string has not yet been found THEN
+    .or_else(|| {
+      //     IF theMessage occurs as the value of result.message (§3.27.11) THEN
+      result.rule.as_ref().and_then(|the_rule| {
+        the_rule.index.and_then(|rule_index| {
+          run.tool.driver.rules.as_ref().and_then(|rules| {
+            //         LET theRule be the reportingDescriptor object (§3.49), an element of theComponent.rules (§3.19.23), which defines the rule that was violated by this result.
+            //         IF theRule exists AND theRule.messageStrings (§3.49.11) is present AND contains a property whose name equals theMessage.id THEN
+            rules.get(rule_index as usize).and_then(|the_rule| {
+              the_rule
+                .message_strings
+                .as_ref()
+                .and_then(|message_strings| {
+                  result.message.id.as_ref().and_then(|message_id| {
+                    //             LET theMFMS be the multiformatMessageString object (§3.12) that is the value of that property.
+                    //             Use the text or markdown property of theMFMS as appropriate.
+                    message_strings
+                      .get(message_id)
+                      .map(|the_mfms| the_mfms.text.clone())
+                  })
+                })
+            })
+          })
+        })
+      })
+    })
+    // IF the string has not yet been found THEN
+    //     IF theComponent.globalMessageStrings (§3.19.22) is present AND contains a property whose name equals theMessage.id THEN
+    //             LET theMFMS be the multiformatMessageString object that is the value of that property.
+    //             Use the text or markdown property of theMFMS as appropriate.
+    .or_else(|| {
+      run.tool.driver.global_message_strings.as_ref().and_then(
+        |global_message_strings| {
+          result.message.id.as_ref().and_then(|message_id| {
+            global_message_strings
+              .get(message_id)
+              .map(|the_mfms| the_mfms.text.clone())
+          })
+        },
+      )
+    })
+  // IF the string has not yet been found THEN
+  //     The lookup procedure fails (which means the SARIF log file is invalid).
+  // .or_else(|| None) # uncesscary but written for illustration
+}
+
+fn resolve_full_description_from_result(
+  rules: &[sarif::ReportingDescriptor],
+  result: &sarif::Result,
+) -> Option<String> {
+  result
+    .rule_index
+    .and_then(|rule_index| {
+      rules.get(rule_index as usize).and_then(|the_descriptor| {
+        the_descriptor
+          .full_description
+          .as_ref()
+          .map(|mfms| mfms.text.clone())
+      })
+    })
+    .or_else(|| {
+      result.rule.as_ref().and_then(|rule| {
+        rule.index.and_then(|rule_index| {
+          rules.get(rule_index as usize).and_then(|the_descriptor| {
+            the_descriptor
+              .full_description
+              .as_ref()
+              .map(|mfms| mfms.text.clone())
+          })
+        })
+      })
+    })
+}
+
+fn resolve_short_description_from_result(
+  rules: &[sarif::ReportingDescriptor],
+  result: &sarif::Result,
+) -> Option<String> {
+  result
+    .rule_index
+    .and_then(|rule_index| {
+      rules.get(rule_index as usize).and_then(|the_descriptor| {
+        the_descriptor
+          .short_description
+          .as_ref()
+          .map(|mfms| mfms.text.clone())
+      })
+    })
+    .or_else(|| {
+      result.rule.as_ref().and_then(|rule| {
+        rule.index.and_then(|rule_index| {
+          rules.get(rule_index as usize).and_then(|the_descriptor| {
+            the_descriptor
+              .short_description
+              .as_ref()
+              .map(|mfms| mfms.text.clone())
+          })
+        })
+      })
+    })
+}
+
+// Prints the diagnostics of a run sorted by file name, emptying `diagnostics`
+fn print_plain(
+  diagnostics: &mut Vec<(String, ResultLevel, usize, usize, String)>,
+) {
+  diagnostics
+    .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+  diagnostics.drain(..).for_each(|diagnostic| {
+    println!(
+      "{}:{}:{}: {}: {}",
+      diagnostic.0, diagnostic.2, diagnostic.3, diagnostic.1, diagnostic.4
+    )
+  });
+}
+
+// Returns the severity of `level`, which orders levels from "none" to "error"
+fn severity(level: ResultLevel) -> u8 {
+  match level {
+    ResultLevel::None => 0,
+    ResultLevel::Note => 1,
+    ResultLevel::Warning => 2,
+    ResultLevel::Error => 3,
+  }
+}
+
+// The results to print, as selected by the filtering arguments; a result is
+// printed if it matches every filter which is given
+struct Filter {
+  level: Option<ResultLevel>,
+  rules: Vec<String>,
+  exclude_rules: Vec<String>,
+  paths: Option<GlobSet>,
+  tools: Vec<String>,
+  kinds: Vec<ResultKind>,
+}
+
+impl Filter {
+  fn new(args: &Args) -> Result<Self> {
+    let paths = if args.path.is_empty() {
+      None
+    } else {
+      let mut builder = GlobSetBuilder::new();
+      for path in &args.path {
+        builder.add(Glob::new(path)?);
+      }
+      Some(builder.build()?)
+    };
+    Ok(Filter {
+      level: args.level,
+      rules: args.rule.clone(),
+      exclude_rules: args.exclude_rule.clone(),
+      paths,
+      tools: args.tool.clone(),
+      kinds: args.kind.clone(),
+    })
+  }
+
+  // Returns whether the results of `run` may be selected by the filter
+  fn matches_tool(&self, run: &sarif::Run) -> bool {
+    self.tools.is_empty()
+      || self
+        .tools
+        .iter()
+        .any(|tool| tool.eq_ignore_ascii_case(&run.tool.driver.name))
+  }
+
+  // Returns whether the results of the rule `rule_id` may be selected by the
+  // filter
+  fn matches_rule(&self, rule_id: Option<&str>) -> bool {
+    let is_rule = |rules: &[String]| {
+      rule_id.is_some_and(|id| rules.iter().any(|rule| rule == id))
+    };
+    (self.rules.is_empty() || is_rule(&self.rules))
+      && !is_rule(&self.exclude_rules)
+  }
+
+  // Returns whether `result` of `run` is selected by the filter. Paths are
+  // matched relative to the current directory, once resolved against
+  // `uri_base_ids`.
+  fn matches(
+    &self,
+    run: &sarif::Run,
+    result: &sarif::Result,
+    uri_base_ids: &BTreeMap<String, sarif::ArtifactLocation>,
+  ) -> bool {
+    let rules = vec![];
+    let rules = run.tool.driver.rules.as_ref().unwrap_or(&rules);
+    let kind = result.kind.as_ref().unwrap_or(&ResultKind::Fail);
+    let is_path = |paths: &GlobSet| {
+      result.locations.iter().flatten().any(|location| {
+        location
+          .physical_location
+          .as_ref()
+          .and_then(|physical_location| {
+            relative_path(physical_location, uri_base_ids)
+          })
+          .is_some_and(|path| paths.is_match(path))
+      })
+    };
+
+    self.level.is_none_or(|level| {
+      severity(resolve_level(rules, run, result)) >= severity(level)
+    }) && self.matches_rule(rule_id(run, result))
+      && self.matches_tool(run)
+      && (self.kinds.is_empty() || self.kinds.contains(kind))
+      && self.paths.as_ref().is_none_or(is_path)
+  }
+}
+
+// The number of printed results of every level, indexed by their severity
+#[derive(Default)]
+struct LevelCounts([usize; 4]);
+
+impl LevelCounts {
+  fn add(&mut self, level: ResultLevel) {
+    self.0[severity(level) as usize] += 1;
+  }
+
+  // Returns the number of results of `level`
+  fn count(&self, level: ResultLevel) -> usize {
+    self.0[severity(level) as usize]
+  }
+
+  // Returns the number of results of `level` or a more severe level
+  fn at_least(&self, level: ResultLevel) -> usize {
+    self.0[severity(level) as usize..].iter().sum()
+  }
+}
+
+fn to_writer_plain(
+  input: Input,
+  src_roots: &[(String, PathBuf)],
+  filter: &Filter,
+) -> Result<LevelCounts> {
+  let mut contents = HashMap::new();
+  let mut diagnostics = vec![];
+  let mut levels = LevelCounts::default();
+  let mut current_run = 0;
+  input.for_each_result(|run_index, run, result| -> Result<()> {
+    let result = &result;
+    let uri_base_ids = uri_base_ids(run, src_roots);
+    if !filter.matches(run, result, &uri_base_ids) {
+      return Ok(());
+    }
+    let mut files = SimpleFiles::new();
+    if run_index != current_run {
+      print_plain(&mut diagnostics);
+      current_run = run_index;
+    }
+    let rules = vec![];
+    let rules = run.tool.driver.rules.as_ref().unwrap_or(&rules);
+    let level = resolve_level(rules, run, result);
+    levels.add(level);
+
+    if let (Some(text), Some(locations)) = (
+      resolve_message_text_from_result(result, run),
+      result.locations.as_ref(),
+    ) {
+      locations.iter().for_each(|location| {
+        if let Some((file_id, range)) = location
+          .physical_location
+          .as_ref()
+          .and_then(|physical_location| {
+            get_label(
+              &mut files,
+              &mut contents,
+              physical_location,
+              run_index,
+              run,
+              &uri_base_ids,
+            )
+          })
+        {
+          if let (Ok(name), Ok(location)) =
+            (files.name(file_id), files.location(file_id, range.start))
+          {
+            let diagnostic = (
+              name.clone(),
+              level,
+              location.line_number,
+              location.column_number,
+              text.clone(),
+            );
+            diagnostics.push(diagnostic);
+          } else {
+            // todo: no location found
+          }
+        }
+      });
+      // todo: no location found
+    }
+
+    Ok(())
+  })?;
+  print_plain(&mut diagnostics);
+
+  Ok(levels)
+}
+
+// Returns the path of the file of `physical_location`, relative to the current
+// directory (the root of the checkout in CI) when it is under it
+fn relative_path(
+  physical_location: &sarif::PhysicalLocation,
+  uri_base_ids: &BTreeMap<String, sarif::ArtifactLocation>,
+) -> Option<String> {
+  let path = try_find_file(physical_location, uri_base_ids)
+    .ok()
+    .or_else(|| {
+      uri::resolve_path(
+        physical_location.artifact_location.as_ref()?,
+        Some(uri_base_ids),
+      )
+      .ok()
+    })?;
+  let path = std::env::current_dir()
+    .ok()
+    .and_then(|current_dir| {
+      path
+        .strip_prefix(&current_dir)
+        .ok()
+        .or_else(|| path.strip_prefix(current_dir.canonicalize().ok()?).ok())
+        .map(Path::to_path_buf)
+    })
+    .unwrap_or(path);
+  Some(path.to_string_lossy().into_owned())
+}
+
+fn to_writer_github(
+  input: Input,
+  src_roots: &[(String, PathBuf)],
+  filter: &Filter,
+) -> Result<LevelCounts> {
+  let mut levels = LevelCounts::default();
+  input.for_each_result(|_, run, result| -> Result<()> {
+    let result = &result;
+    let uri_base_ids = uri_base_ids(run, src_roots);
+    if !filter.matches(run, result, &uri_base_ids) {
+      return Ok(());
+    }
+    let rules = vec![];
+    let rules = run.tool.driver.rules.as_ref().unwrap_or(&rules);
+    let level = resolve_level(rules, run, result);
+    levels.add(level);
+
+    let mut annotation = github::Annotation {
+      level: Some(level),
+      title: rule_id(run, result).map(String::from),
+      message: resolve_message_text_from_result(result, run)
+        .unwrap_or_default(),
+      ..Default::default()
+    };
+    // the result is annotated at its first location in a file
+    if let Some(physical_location) = result
+      .locations
+      .iter()
+      .flatten()
+      .find_map(|location| location.physical_location.as_ref())
+    {
+      annotation.file = relative_path(physical_location, &uri_base_ids);
+      if let Some(region) = physical_location.region.as_ref() {
+        annotation.line = region.start_line;
+        annotation.end_line = region.end_line;
+        annotation.col = region.start_column;
+        annotation.end_column = region.end_column;
+      }
+    }
+    println!("{}", annotation);
+    Ok(())
+  })?;
+
+  Ok(levels)
+}
+
+// Returns the fingerprint of a GitLab issue, from the fingerprints of the
+// result if it has any, else hashed from its rule, path, line and message
+fn resolve_fingerprint(
+  result: &sarif::Result,
+  rule_id: Option<&str>,
+  location: &gitlab::Location,
+  description: &str,
+) -> String {
+  result
+    .fingerprints
+    .iter()
+    .chain(result.partial_fingerprints.iter())
+    .find_map(|fingerprints| {
+      fingerprints
+        .get(fingerprint::PRIMARY_LOCATION_LINE_HASH)
+        .or_else(|| fingerprints.values().next())
+    })
+    .cloned()
+    .unwrap_or_else(|| {
+      fingerprint::fingerprint(
+        rule_id,
+        &location.path,
+        &format!("{} {}", location.lines.begin, description),
+      )
+    })
+}
+
+fn to_writer_gitlab(
+  input: Input,
+  src_roots: &[(String, PathBuf)],
+  filter: &Filter,
+) -> Result<LevelCounts> {
+  let mut levels = LevelCounts::default();
+  let mut issues = vec![];
+  input.for_each_result(|_, run, result| -> Result<()> {
+    let result = &result;
+    let uri_base_ids = uri_base_ids(run, src_roots);
+    if !filter.matches(run, result, &uri_base_ids) {
+      return Ok(());
+    }
+    let rules = vec![];
+    let rules = run.tool.driver.rules.as_ref().unwrap_or(&rules);
+    let level = resolve_level(rules, run, result);
+    levels.add(level);
+
+    // issues are located at the first location of their result in a file,
+    // as GitLab requires a path
+    let location = match result
+      .locations
+      .iter()
+      .flatten()
+      .filter_map(|location| location.physical_location.as_ref())
+      .find_map(|physical_location| {
+        Some(gitlab::Location {
+          path: relative_path(physical_location, &uri_base_ids)?,
+          lines: gitlab::Lines {
+            begin: physical_location
+              .region
+              .as_ref()
+              .and_then(|region| region.start_line)
+              .unwrap_or(1),
+          },
+        })
+      }) {
+      Some(location) => location,
+      None => return Ok(()),
+    };
+    let rule_id = rule_id(run, result);
+    let description =
+      resolve_message_text_from_result(result, run).unwrap_or_default();
+    issues.push(gitlab::Issue {
+      fingerprint: resolve_fingerprint(
+        result,
+        rule_id,
+        &location,
+        &description,
+      ),
+      check_name: rule_id.unwrap_or(&run.tool.driver.name).to_string(),
+      description,
+      severity: level.into(),
+      location,
+    });
+    Ok(())
+  })?;
+  let mut writer = std::io::stdout().lock();
+  serde_json::to_writer_pretty(&mut writer, &issues)?;
+  writeln!(writer)?;
+
+  Ok(levels)
+}
+
+fn to_writer_junit(
+  input: Input,
+  src_roots: &[(String, PathBuf)],
+  filter: &Filter,
+) -> Result<LevelCounts> {
+  // every rule of the driver is a test case, which passes unless it has
+  // results, and so is every rule which only appears in results. The test
+  // suite of a run is made at its first result, or once every result is read
+  // for runs without any.
+  let test_suite = |run: &sarif::Run| {
+    filter.matches_tool(run).then(|| junit::TestSuite {
+      name: run.tool.driver.name.clone(),
+      test_cases: run
+        .tool
+        .driver
+        .rules
+        .iter()
+        .flatten()
+        .filter(|rule| filter.matches_rule(Some(&rule.id)))
+        .map(|rule| junit::TestCase {
+          name: rule.id.clone(),
+          failures: vec![],
+        })
+        .collect(),
+    })
+  };
+  let mut test_suites: BTreeMap<usize, Option<junit::TestSuite>> =
+    BTreeMap::new();
+  let mut levels = LevelCounts::default();
+  let runs = input.for_each_result(|run_index, run, result| -> Result<()> {
+    let result = &result;
+    let uri_base_ids = uri_base_ids(run, src_roots);
+    if !filter.matches(run, result, &uri_base_ids) {
+      return Ok(());
+    }
+    let rules = vec![];
+    let rules = run.tool.driver.rules.as_ref().unwrap_or(&rules);
+    let level = resolve_level(rules, run, result);
+    levels.add(level);
+    // results which are not failures (ex. of kind "pass") resolve to "none"
+    if level == ResultLevel::None {
+      return Ok(());
+    }
+
+    let message =
+      resolve_message_text_from_result(result, run).unwrap_or_default();
+    let location = result
+      .locations
+      .iter()
+      .flatten()
+      .filter_map(|location| location.physical_location.as_ref())
+      .find_map(|physical_location| {
+        let path = relative_path(physical_location, &uri_base_ids)?;
+        Some(
+          match physical_location
+            .region
+            .as_ref()
+            .and_then(|region| region.start_line)
+          {
+            Some(line) => format!("{}:{}", path, line),
+            None => path,
+          },
+        )
+      });
+    let failure = junit::Failure {
+      text: match location {
+        Some(location) => format!("{}: {}", location, message),
+        None => message.clone(),
+      },
+      message,
+      kind: level.to_string(),
+    };
+
+    // results without a rule are failures of a test case named after the tool
+    let name = rule_id(run, result).unwrap_or(&run.tool.driver.name);
+    let test_cases = match test_suites
+      .entry(run_index)
+      .or_insert_with(|| test_suite(run))
+      .as_mut()
+    {
+      Some(test_suite) => &mut test_suite.test_cases,
+      None => return Ok(()),
+    };
+    match test_cases
+      .iter_mut()
+      .find(|test_case| test_case.name == name)
+    {
+      Some(test_case) => test_case.failures.push(failure),
+      None => test_cases.push(junit::TestCase {
+        name: name.to_string(),
+        failures: vec![failure],
+      }),
+    }
+    Ok(())
+  })?;
+  runs.iter().enumerate().for_each(|(index, run)| {
+    test_suites.entry(index).or_insert_with(|| test_suite(run));
+  });
+  let test_suites: Vec<junit::TestSuite> =
+    test_suites.into_values().flatten().collect();
+  junit::write_report(std::io::stdout().lock(), &test_suites)?;
+
+  Ok(levels)
+}
+
+fn to_writer_checkstyle(
+  input: Input,
+  src_roots: &[(String, PathBuf)],
+  filter: &Filter,
+) -> Result<LevelCounts> {
+  let mut levels = LevelCounts::default();
+  let mut files: BTreeMap<String, Vec<checkstyle::Error>> = BTreeMap::new();
+  input.for_each_result(|_, run, result| -> Result<()> {
+    let result = &result;
+    let uri_base_ids = uri_base_ids(run, src_roots);
+    if !filter.matches(run, result, &uri_base_ids) {
+      return Ok(());
+    }
+    let rules = vec![];
+    let rules = run.tool.driver.rules.as_ref().unwrap_or(&rules);
+    let level = resolve_level(rules, run, result);
+    levels.add(level);
+
+    // results are reported in the file of their first location, as
+    // Checkstyle has no errors outside of files
+    let (path, region) = match result
+      .locations
+      .iter()
+      .flatten()
+      .filter_map(|location| location.physical_location.as_ref())
+      .find_map(|physical_location| {
+        Some((
+          relative_path(physical_location, &uri_base_ids)?,
+          physical_location.region.as_ref(),
+        ))
+      }) {
+      Some(location) => location,
+      None => return Ok(()),
+    };
+    files.entry(path).or_default().push(checkstyle::Error {
+      line: region.and_then(|region| region.start_line),
+      column: region.and_then(|region| region.start_column),
+      severity: checkstyle::severity(level),
+      message: resolve_message_text_from_result(result, run)
+        .unwrap_or_default(),
+      source: rule_id(run, result)
+        .unwrap_or(&run.tool.driver.name)
+        .to_string(),
+    });
+    Ok(())
+  })?;
+  checkstyle::write_report(std::io::stdout().lock(), &files)?;
+
+  Ok(levels)
+}
+
+fn to_writer_pretty(
+  input: Input,
+  color: ColorOption,
+  src_roots: &[(String, PathBuf)],
+  collapse_unimportant: bool,
+  filter: &Filter,
+) -> Result<LevelCounts> {
+  let color_choice = match color {
+    ColorOption::Always => ColorChoice::Always,
+    ColorOption::AlwaysAnsi => ColorChoice::AlwaysAnsi,
+    ColorOption::Auto => ColorChoice::Auto,
+    ColorOption::Never => ColorChoice::Never,
+  };
+
+  let mut writer = StandardStream::stdout(color_choice);
+  let mut contents = HashMap::new();
+  let config = codespan_reporting::term::Config::default();
+  let mut levels = LevelCounts::default();
+  input.for_each_result(|run_index, run, result| -> Result<()> {
+    let result = &result;
+    let uri_base_ids = uri_base_ids(run, src_roots);
+    if !filter.matches(run, result, &uri_base_ids) {
+      return Ok(());
+    }
+    let mut files = SimpleFiles::new();
+    let rules = vec![];
+    let rules = run.tool.driver.rules.as_ref().unwrap_or(&rules);
+    let level = resolve_level(rules, run, result);
+    levels.add(level);
+    let mut diagnostic: Diagnostic<usize> = Diagnostic::new(match level {
+      ResultLevel::Note => diagnostic::Severity::Note,
+      ResultLevel::Warning => diagnostic::Severity::Warning,
+      ResultLevel::Error => diagnostic::Severity::Error,
+      _ => diagnostic::Severity::Warning,
+    });
+    if let Some(message) = resolve_message_text_from_result(result, run) {
+      diagnostic.message = message;
+    }
+    if let Some(text) = resolve_short_description_from_result(rules, result) {
+      diagnostic.notes.push(text);
+    }
+    if let Some(text) = resolve_full_description_from_result(rules, result) {
+      diagnostic.notes.push(text);
+    }
+
+    if let Some(locations) = result.locations.as_ref() {
+      locations.iter().for_each(|location| {
+        if let Some((file_id, range)) = location
+          .physical_location
+          .as_ref()
+          .and_then(|physical_location| {
+            get_label(
+              &mut files,
+              &mut contents,
+              physical_location,
+              run_index,
+              run,
+              &uri_base_ids,
+            )
+          })
+        {
+          diagnostic.labels.push(Label::primary(file_id, range));
+        }
+      });
+    }
+
+    if let Some(locations) = result.related_locations.as_ref() {
+      locations.iter().for_each(|location| {
+        if let Some((file_id, range, message)) = location
+          .physical_location
+          .as_ref()
+          .and_then(|physical_location| {
+            get_label(
+              &mut files,
+              &mut contents,
+              physical_location,
+              run_index,
+              run,
+              &uri_base_ids,
+            )
+            .map(|(file_id, range)| {
+              (
+                file_id,
+                range,
+                location.message.as_ref().and_then(|x| x.text.clone()),
+              )
+            })
+          })
+        {
+          diagnostic.labels.push(
+            Label::secondary(file_id, range)
+              .with_message(message.unwrap_or("".to_string())),
+          );
+        }
+      });
+    }
+
+    let code_flows = resolve_code_flows(
+      &mut files,
+      &mut contents,
+      run_index,
+      run,
+      &uri_base_ids,
+      result,
+      collapse_unimportant,
+    );
+    let stacks = resolve_stacks(
+      &mut files,
+      &mut contents,
+      run_index,
+      run,
+      &uri_base_ids,
+      result,
+    );
+    term::emit(&mut writer.lock(), &config, &files, &diagnostic)?;
+    code_flows
+      .iter()
+      .chain(stacks.iter())
+      .try_for_each(|note| {
+        term::emit(&mut writer.lock(), &config, &files, note)
+      })?;
+    Ok(())
+  })?;
+
+  // the summary counts results by their level, so results without a level
+  // (ex. of kind "pass"), although printed as warnings, are not counted
+  let warnings = levels.count(ResultLevel::Warning);
+  if warnings > 0 {
+    writer
+      .set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
+    writer.write_all("warning".as_bytes())?;
+    writer.reset()?;
+    writer.set_color(ColorSpec::new().set_bold(true))?;
+    writer
+      .write_all(format!(": {} warnings emitted\n", warnings).as_bytes())?;
+    writer.reset()?;
+  }
+
+  let errors = levels.count(ResultLevel::Error);
+  if errors > 0 {
+    writer
+      .set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
+    writer.write_all("error".as_bytes())?;
+    writer.reset()?;
+    writer.set_color(ColorSpec::new().set_bold(true))?;
+    writer.write_all(format!(": {} errors emitted\n", errors).as_bytes())?;
+    writer.reset()?;
+  }
+
+  Ok(levels)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum MessageFormat {
+  Plain,
+  Pretty,
+  /// Workflow commands annotating the results in GitHub Actions
+  Github,
+  /// A GitLab Code Quality report
+  Gitlab,
+  /// A JUnit XML report, with a test case per rule
+  Junit,
+  /// A Checkstyle XML report, with the results grouped by file
+  Checkstyle,
+}
+
+/// The least severe level of the printed results which fails `sarif-fmt`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum FailOn {
+  /// Never fail
+  None,
+  Note,
+  Warning,
+  Error,
+}
+
+/// Read the docs of termcolor's ColorChoice
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum ColorOption {
+  Always,
+  AlwaysAnsi,
+  Auto,
+  Never,
+}
+
+/// Command line arguments of `sarif-fmt`
+#[derive(Parser, Debug)]
+#[command(
+  version,
+  about = "Pretty print SARIF results",
+  after_help = "The expected input is a SARIF file (ex. cat foo.sarif | sarif-fmt).",
+  long_about = None
+)]
+pub struct Args {
+  /// One of plain, pretty, github, gitlab, junit or checkstyle
+  #[arg(short, long, value_enum, default_value = "pretty")]
+  message_format: MessageFormat,
+  /// input file; reads from stdin if none is given
+  #[arg(short, long)]
+  input: Option<std::path::PathBuf>,
+  /// Allows to override coloring engine, e.g. to force color in CI/CD environments
+  #[arg(short, long, value_enum, default_value = "auto")]
+  color: ColorOption,
+  /// Check the input against the SARIF schema and specification, and fail
+  /// with the violations found instead of printing an invalid log
+  #[arg(long)]
+  validate: bool,
+  /// Resolve the uriBaseId NAME of artifact URIs to PATH instead of its
+  /// definition in originalUriBaseIds (ex. %SRCROOT%=.); may be repeated
+  #[arg(long, value_name = "NAME=PATH", value_parser = parse_src_root)]
+  src_root: Vec<(String, PathBuf)>,
+  /// Leave the steps of code flows marked as unimportant out of their trace
+  #[arg(long)]
+  collapse_unimportant: bool,
+  /// Only print results of LEVEL or a more severe level (none, note, warning
+  /// or error), as resolved from the result, its rule and the run
+  #[arg(long, value_name = "LEVEL", value_parser = ResultLevel::from_str)]
+  level: Option<ResultLevel>,
+  /// Only print results of the rule with this id; may be repeated
+  #[arg(long, value_name = "RULE")]
+  rule: Vec<String>,
+  /// Leave out results of the rule with this id; may be repeated
+  #[arg(long, value_name = "RULE")]
+  exclude_rule: Vec<String>,
+  /// Only print results located in a file matching this glob (ex. 'src/**'),
+  /// relative to the current directory; may be repeated
+  #[arg(long, value_name = "GLOB")]
+  path: Vec<String>,
+  /// Only print results of runs of the tool with this name (ex. shellcheck);
+  /// may be repeated
+  #[arg(long, value_name = "TOOL")]
+  tool: Vec<String>,
+  /// Only print results of this kind (ex. fail, review or open); may be
+  /// repeated
+  #[arg(long, value_name = "KIND", value_parser = ResultKind::from_str)]
+  kind: Vec<ResultKind>,
+  /// Exit with a non-zero status if any printed result has this level or a
+  /// more severe one, as resolved from the result, its rule and the run
+  #[arg(long, value_enum, value_name = "LEVEL", default_value = "none")]
+  fail_on: FailOn,
+  /// Exit with a non-zero status if more than N warnings are printed
+  #[arg(long, value_name = "N")]
+  max_warnings: Option<usize>,
+}
+
+// Fails if the printed results break the policy of `--fail-on` or
+// `--max-warnings`
+fn check_exit_policy(
+  levels: &LevelCounts,
+  fail_on: FailOn,
+  max_warnings: Option<usize>,
+) -> Result<()> {
+  let fail_level = match fail_on {
+    FailOn::None => None,
+    FailOn::Note => Some(ResultLevel::Note),
+    FailOn::Warning => Some(ResultLevel::Warning),
+    FailOn::Error => Some(ResultLevel::Error),
+  };
+  if let Some(level) = fail_level {
+    let count = levels.at_least(level);
+    if count > 0 {
+      anyhow::bail!(
+        "found {} results of level {} or above (--fail-on {})",
+        count,
+        level,
+        level
+      );
+    }
+  }
+  if let Some(max_warnings) = max_warnings {
+    let warnings = levels.count(ResultLevel::Warning);
+    if warnings > max_warnings {
+      anyhow::bail!(
+        "found {} warnings, more than the {} allowed by --max-warnings",
+        warnings,
+        max_warnings
+      );
+    }
+  }
+  Ok(())
+}
+
+/// Reads the SARIF input and prints it in the requested message format
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments
+pub fn run(args: Args) -> Result<()> {
+  let filter = Filter::new(&args)?;
+  let mut input = Input::open(args.input.as_deref())?;
+  if args.validate {
+    input = input.validate()?;
+  }
+  let levels = match args.message_format {
+    MessageFormat::Plain => to_writer_plain(input, &args.src_root, &filter)?,
+    MessageFormat::Github => to_writer_github(input, &args.src_root, &filter)?,
+    MessageFormat::Gitlab => to_writer_gitlab(input, &args.src_root, &filter)?,
+    MessageFormat::Junit => to_writer_junit(input, &args.src_root, &filter)?,
+    MessageFormat::Checkstyle => {
+      to_writer_checkstyle(input, &args.src_root, &filter)?
+    }
+    MessageFormat::Pretty => to_writer_pretty(
+      input,
+      args.color,
+      &args.src_root,
+      args.collapse_unimportant,
+      &filter,
+    )?,
+  };
+  check_exit_policy(&levels, args.fail_on, args.max_warnings)
+}
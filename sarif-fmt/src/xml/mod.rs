@@ -0,0 +1,32 @@
+// Helpers to write the XML reports of sarif-fmt
+
+// Escapes `value` to be written as the text of an element or the value of an
+// attribute
+pub(crate) fn escape(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  value.chars().for_each(|c| match c {
+    '&' => escaped.push_str("&amp;"),
+    '<' => escaped.push_str("&lt;"),
+    '>' => escaped.push_str("&gt;"),
+    '"' => escaped.push_str("&quot;"),
+    '\'' => escaped.push_str("&apos;"),
+    // control characters other than whitespace are not allowed in XML 1.0
+    c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+    c => escaped.push(c),
+  });
+  escaped
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_escape() {
+    assert_eq!(
+      escape("if a < b && c > \"d\" {}"),
+      "if a &lt; b &amp;&amp; c &gt; &quot;d&quot; {}"
+    );
+    assert_eq!(escape("don't\u{1b}[0m\n"), "don&apos;t[0m\n");
+  }
+}
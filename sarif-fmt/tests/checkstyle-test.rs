@@ -0,0 +1,39 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+mod common;
+
+#[test]
+// Test that results are exported as a Checkstyle XML report
+fn test_checkstyle() -> Result<()> {
+  let sarif_fmt_bin = common::sarif_fmt_bin()?;
+  let sarif =
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/filter.sarif");
+
+  let output = duct::cmd!(
+    &sarif_fmt_bin,
+    "-m",
+    "checkstyle",
+    "--exclude-rule",
+    "clippy::useless_vec",
+    "-i",
+    &sarif
+  )
+  .read()?;
+  // results are grouped by file, in the order of their paths
+  assert_eq!(
+    output,
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="scripts/build.sh">
+    <error line="2" severity="ignore" message="Double quote to prevent globbing" source="SC2086"/>
+    <error line="5" severity="error" message="Couldn&apos;t parse this test expression" source="SC1073"/>
+  </file>
+  <file name="src/main.rs">
+    <error line="3" severity="warning" message="used `unwrap()` on an `Option` value" source="clippy::unwrap_used"/>
+  </file>
+</checkstyle>"#
+  );
+
+  Ok(())
+}
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+#[test]
+// Test that code flows are rendered as numbered steps
+fn test_code_flow() -> Result<()> {
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let cargo_workspace_directory = fs::canonicalize(PathBuf::from_iter(
+    [cargo_manifest_directory.clone(), PathBuf::from("..")].iter(),
+  ))?;
+
+  duct_sh::sh("cargo build --bin sarif-fmt")
+    .dir(cargo_workspace_directory.clone())
+    .run()?;
+
+  let sarif_fmt_bin = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./target/debug/sarif-fmt"),
+    ]
+    .iter(),
+  ))?;
+
+  let sarif = cargo_manifest_directory.join("tests/data/code-flow.sarif");
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+
+  let output = duct::cmd!(&sarif_fmt_bin, "-i", &sarif)
+    .full_env(&env_map)
+    .read()?;
+  assert!(output.contains("note: code flow 1: argv flows into system"));
+  // essential steps are highlighted as primary labels
+  assert!(output.contains("^^^^^^^ 1. user input enters here"));
+  // nested steps are indented
+  assert!(output.contains("-----   2. copied"));
+  assert!(output.contains("^^^^^^ 3. reaches the command"));
+  assert!(output.contains("error: 1 errors emitted"));
+
+  let output =
+    duct::cmd!(&sarif_fmt_bin, "--collapse-unimportant", "-i", &sarif)
+      .full_env(&env_map)
+      .read()?;
+  assert!(output.contains("1. user input enters here"));
+  assert!(!output.contains("2. copied"));
+  assert!(output.contains("3. reaches the command"));
+  assert!(output.contains("= 1 unimportant steps collapsed"));
+
+  Ok(())
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+#[test]
+// Test that regions of non-ASCII sources are highlighted in their column kind
+fn test_column_kind() -> Result<()> {
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let cargo_workspace_directory = fs::canonicalize(PathBuf::from_iter(
+    [cargo_manifest_directory.clone(), PathBuf::from("..")].iter(),
+  ))?;
+
+  duct_sh::sh("cargo build --bin sarif-fmt")
+    .dir(cargo_workspace_directory.clone())
+    .run()?;
+
+  let sarif_fmt_bin = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./target/debug/sarif-fmt"),
+    ]
+    .iter(),
+  ))?;
+
+  let sarif = cargo_manifest_directory.join("tests/data/column-kind.sarif");
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+
+  let output =
+    duct::cmd!(&sarif_fmt_bin, "-m", "plain", "-i", &sarif).read()?;
+  let lines: Vec<&str> = output.lines().collect();
+  assert_eq!(
+    lines,
+    vec![
+      // é, after 𝄞 which spans two UTF-16 code units
+      "gone/unicode.rs:2:13: warning: utf-16 columns",
+      // 𝄞
+      "gone/unicode.rs:2:12: warning: character offset",
+      // é, in a run counting columns in code points
+      "gone/unicode.rs:2:13: warning: code point columns",
+    ]
+  );
+
+  let output = duct::cmd!(&sarif_fmt_bin, "-i", &sarif)
+    .full_env(&env_map)
+    .read()?;
+  assert!(output.contains("2 │   let s = \"𝄞é\"; // 𝄞\n  │             ^\n"));
+  assert!(output.contains("2 │   let s = \"𝄞é\"; // 𝄞\n  │            ^\n"));
+
+  Ok(())
+}
@@ -0,0 +1,15 @@
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+
+// Returns the path of the sarif-fmt binary, after building it
+pub fn sarif_fmt_bin() -> Result<PathBuf> {
+  let cargo_workspace_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."))?;
+  duct_sh::sh("cargo build --bin sarif-fmt")
+    .dir(&cargo_workspace_directory)
+    .run()?;
+  Ok(fs::canonicalize(
+    cargo_workspace_directory.join("target/debug/sarif-fmt"),
+  )?)
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+#[test]
+// Test that files which are not on disk are rendered from the log
+fn test_embedded_contents() -> Result<()> {
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let cargo_workspace_directory = fs::canonicalize(PathBuf::from_iter(
+    [cargo_manifest_directory.clone(), PathBuf::from("..")].iter(),
+  ))?;
+
+  duct_sh::sh("cargo build --bin sarif-fmt")
+    .dir(cargo_workspace_directory.clone())
+    .run()?;
+
+  let sarif_fmt_bin = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./target/debug/sarif-fmt"),
+    ]
+    .iter(),
+  ))?;
+
+  let sarif =
+    cargo_manifest_directory.join("tests/data/embedded-contents.sarif");
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+
+  let output = duct::cmd!(&sarif_fmt_bin, "-i", &sarif)
+    .dir(&cargo_workspace_directory)
+    .full_env(&env_map)
+    .read()?;
+
+  // region.snippet
+  assert!(output.contains("gone/region.rs:10:5"));
+  assert!(output.contains("10 │     let unused = 1;"));
+  assert!(output.contains("│     ^^^^^^^^^^^^^^^"));
+  // contextRegion.snippet
+  assert!(output.contains("gone/context.rs:3:6"));
+  assert!(output.contains("3 │   if 100 > i32::MAX {}"));
+  // artifacts[].contents.binary
+  assert!(output.contains("gone/artifact.rs:2:7"));
+  assert!(output.contains("2 │   let answer = 42;"));
+
+  Ok(())
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+mod common;
+
+#[test]
+// Test that the exit status follows --fail-on and --max-warnings
+fn test_exit_policy() -> Result<()> {
+  let sarif_fmt_bin = common::sarif_fmt_bin()?;
+  let sarif =
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/filter.sarif");
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+  env_map.remove("RUST_BACKTRACE");
+  env_map.remove("RUST_LIB_BACKTRACE");
+
+  // returns the stderr of a failing run, or None if it succeeds
+  let check = |args: &[&str]| -> Result<Option<String>> {
+    let mut args: Vec<&str> = args.to_vec();
+    args.extend(["-i", sarif.to_str().unwrap()]);
+    let output = duct::cmd(&sarif_fmt_bin, args)
+      .full_env(&env_map)
+      .stdout_null()
+      .stderr_capture()
+      .unchecked()
+      .run()?;
+    Ok(
+      (!output.status.success())
+        .then(|| String::from_utf8_lossy(&output.stderr).into_owned()),
+    )
+  };
+
+  assert_eq!(check(&[])?, None);
+  assert_eq!(check(&["--fail-on", "none"])?, None);
+  assert!(check(&["--fail-on", "error"])?
+    .unwrap()
+    .contains("found 1 results of level error or above (--fail-on error)"));
+  assert!(check(&["-m", "plain", "--fail-on", "note"])?
+    .unwrap()
+    .contains("found 3 results of level note or above"));
+  // only the printed results are counted
+  assert_eq!(check(&["--fail-on", "error", "--tool", "clippy"])?, None);
+  assert!(check(&["--max-warnings", "0"])?
+    .unwrap()
+    .contains("found 1 warnings, more than the 0 allowed by --max-warnings"));
+  assert_eq!(check(&["--max-warnings", "1"])?, None);
+
+  Ok(())
+}
+
+#[test]
+// Test that the summary counts the printed results by their level, leaving out
+// results without a level
+fn test_summary() -> Result<()> {
+  let sarif_fmt_bin = common::sarif_fmt_bin()?;
+  let sarif =
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/filter.sarif");
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+
+  let output = duct::cmd!(sarif_fmt_bin, "-i", sarif)
+    .full_env(&env_map)
+    .read()?;
+
+  assert!(output.contains("warning: 1 warnings emitted\n"));
+  assert!(output.contains("error: 1 errors emitted"));
+
+  Ok(())
+}
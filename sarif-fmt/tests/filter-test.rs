@@ -0,0 +1,80 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+mod common;
+
+#[test]
+// Test that results are filtered by level, rule, path, tool and kind
+fn test_filter() -> Result<()> {
+  let sarif_fmt_bin = common::sarif_fmt_bin()?;
+  let sarif =
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/filter.sarif");
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+
+  let filter = |args: &[&str]| -> Result<Vec<String>> {
+    let mut args: Vec<&str> = args.to_vec();
+    args.extend(["-m", "plain", "-i", sarif.to_str().unwrap()]);
+    let output = duct::cmd(&sarif_fmt_bin, args).full_env(&env_map).read()?;
+    Ok(output.lines().map(String::from).collect())
+  };
+
+  assert_eq!(filter(&[])?.len(), 4);
+  assert_eq!(
+    filter(&["--level", "warning"])?,
+    vec![
+      "src/main.rs:3:1: warning: used `unwrap()` on an `Option` value",
+      "scripts/build.sh:5:1: error: Couldn't parse this test expression",
+    ]
+  );
+  assert_eq!(
+    filter(&["--rule", "clippy::unwrap_used", "--rule", "SC1073"])?,
+    vec![
+      "src/main.rs:3:1: warning: used `unwrap()` on an `Option` value",
+      "scripts/build.sh:5:1: error: Couldn't parse this test expression",
+    ]
+  );
+  assert!(!filter(&["--exclude-rule", "SC2086"])?
+    .iter()
+    .any(|line| line.contains("Double quote")));
+  assert_eq!(
+    filter(&["--path", "src/**"])?,
+    vec!["src/main.rs:3:1: warning: used `unwrap()` on an `Option` value"]
+  );
+  assert_eq!(filter(&["--tool", "shellcheck"])?.len(), 2);
+  assert_eq!(
+    filter(&["--kind", "review"])?,
+    vec!["scripts/build.sh:2:1: none: Double quote to prevent globbing"]
+  );
+  // filters are combined
+  assert_eq!(filter(&["--tool", "clippy", "--level", "error"])?.len(), 0);
+
+  // paths are matched once their uriBaseId is resolved
+  let manifest_directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+  let uri_base_id = |path: &str| -> Result<usize> {
+    let output = duct::cmd!(
+      &sarif_fmt_bin,
+      "--src-root",
+      format!(
+        "TESTS={}",
+        manifest_directory.join("tests").to_str().unwrap()
+      ),
+      "--path",
+      path,
+      "-m",
+      "plain",
+      "-i",
+      manifest_directory.join("tests/data/uri-base-id.sarif")
+    )
+    .dir(&manifest_directory)
+    .full_env(&env_map)
+    .read()?;
+    Ok(output.lines().count())
+  };
+  assert_eq!(uri_base_id("tests/data/src/**")?, 1);
+  assert_eq!(uri_base_id("src/**")?, 0);
+
+  Ok(())
+}
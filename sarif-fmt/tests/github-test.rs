@@ -0,0 +1,34 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+mod common;
+
+#[test]
+// Test that results are printed as GitHub Actions workflow commands
+fn test_github() -> Result<()> {
+  let sarif_fmt_bin = common::sarif_fmt_bin()?;
+  let sarif =
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/filter.sarif");
+
+  let output = duct::cmd!(
+    &sarif_fmt_bin,
+    "-m",
+    "github",
+    "--level",
+    "warning",
+    "-i",
+    &sarif
+  )
+  .read()?;
+  assert_eq!(
+    output.lines().collect::<Vec<_>>(),
+    vec![
+      "::warning file=src/main.rs,line=3,title=clippy%3A%3Aunwrap_used::used \
+       `unwrap()` on an `Option` value",
+      "::error file=scripts/build.sh,line=5,title=SC1073::Couldn't parse this \
+       test expression",
+    ]
+  );
+
+  Ok(())
+}
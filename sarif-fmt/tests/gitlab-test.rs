@@ -0,0 +1,34 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+mod common;
+
+#[test]
+// Test that results are exported as a GitLab Code Quality report
+fn test_gitlab() -> Result<()> {
+  let sarif_fmt_bin = common::sarif_fmt_bin()?;
+  let sarif =
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/filter.sarif");
+
+  let output =
+    duct::cmd!(&sarif_fmt_bin, "-m", "gitlab", "-i", &sarif).read()?;
+  let issues: Vec<serde_json::Value> = serde_json::from_str(&output)?;
+  assert_eq!(issues.len(), 4);
+  assert_eq!(
+    issues[0],
+    serde_json::json!({
+      "description": "used `unwrap()` on an `Option` value",
+      "check_name": "clippy::unwrap_used",
+      "fingerprint": issues[0]["fingerprint"],
+      "severity": "minor",
+      "location": { "path": "src/main.rs", "lines": { "begin": 3 } }
+    })
+  );
+  // fingerprints are unique, and reuse the fingerprints of the log
+  assert_ne!(issues[2]["fingerprint"], issues[3]["fingerprint"]);
+  assert_eq!(issues[3]["fingerprint"], "5d4f2a8c0b1e7f39:1");
+  assert_eq!(issues[3]["severity"], "major");
+  assert_eq!(issues[1]["severity"], "info");
+
+  Ok(())
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+mod common;
+
+#[test]
+// Test that results are exported as a JUnit XML report
+fn test_junit() -> Result<()> {
+  let sarif_fmt_bin = common::sarif_fmt_bin()?;
+  let sarif =
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/filter.sarif");
+
+  let output =
+    duct::cmd!(&sarif_fmt_bin, "-m", "junit", "-i", &sarif).read()?;
+  // stdin, which cannot be sought, is read in memory first
+  assert_eq!(
+    duct::cmd!(&sarif_fmt_bin, "-m", "junit")
+      .stdin_path(&sarif)
+      .read()?,
+    output
+  );
+  assert_eq!(
+    duct::cmd!(&sarif_fmt_bin, "-m", "junit", "--validate")
+      .stdin_path(&sarif)
+      .read()?,
+    output
+  );
+  assert!(output.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+  assert!(output.contains(r#"<testsuites tests="4" failures="3">"#));
+  assert!(
+    output.contains(r#"<testsuite name="clippy" tests="2" failures="2">"#)
+  );
+  assert!(output.contains(
+    r#"<failure message="used `unwrap()` on an `Option` value" type="warning">src/main.rs:3: used `unwrap()` on an `Option` value</failure>"#
+  ));
+  // results of level none (ex. reviews) are not failures
+  assert!(!output.contains("SC2086"));
+  assert!(output.contains(
+    r#"<failure message="Couldn&apos;t parse this test expression" type="error">"#
+  ));
+  // the rules of a run without results are passing test cases
+  assert!(output.contains(r#"<testcase name="DL3008" classname="hadolint"/>"#));
+
+  let output = duct::cmd!(
+    &sarif_fmt_bin,
+    "-m",
+    "junit",
+    "--tool",
+    "hadolint",
+    "-i",
+    &sarif
+  )
+  .read()?;
+  assert!(output.contains(r#"<testsuites tests="1" failures="0">"#));
+
+  Ok(())
+}
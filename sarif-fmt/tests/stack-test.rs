@@ -0,0 +1,45 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+#[test]
+// Test that stacks are rendered as numbered frames
+fn test_stack() -> Result<()> {
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let cargo_workspace_directory = fs::canonicalize(PathBuf::from_iter(
+    [cargo_manifest_directory.clone(), PathBuf::from("..")].iter(),
+  ))?;
+
+  duct_sh::sh("cargo build --bin sarif-fmt")
+    .dir(cargo_workspace_directory.clone())
+    .run()?;
+
+  let sarif_fmt_bin = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./target/debug/sarif-fmt"),
+    ]
+    .iter(),
+  ))?;
+
+  let sarif = cargo_manifest_directory.join("tests/data/stack.sarif");
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+
+  let output = duct::cmd!(&sarif_fmt_bin, "-i", &sarif)
+    .full_env(&env_map)
+    .read()?;
+  assert!(output.contains("note: stack 1: BACKTRACE"));
+  // the innermost frame is highlighted as the primary label
+  assert!(output.contains("^^ 1. deref"));
+  assert!(output.contains("-------- 2. main"));
+  // frames without a location are listed as notes
+  assert!(output.contains("= 3. std::rt::lang_start in std"));
+  assert!(output.contains("error: 1 errors emitted"));
+
+  Ok(())
+}
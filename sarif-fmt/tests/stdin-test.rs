@@ -0,0 +1,38 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+mod common;
+
+#[test]
+// Test that run members following the results are read from stdin
+fn test_stdin_trailing_members() -> Result<()> {
+  let sarif_fmt_bin = common::sarif_fmt_bin()?;
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let sarif =
+    cargo_manifest_directory.join("tests/data/trailing-members.sarif");
+  let src_root = format!(
+    "TESTS={}",
+    cargo_manifest_directory.join("tests").to_str().unwrap()
+  );
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+
+  let output =
+    duct::cmd!(&sarif_fmt_bin, "--src-root", &src_root, "-i", &sarif)
+      .full_env(&env_map)
+      .read()?;
+  assert!(output.contains("if vec.len() <= 0 {}"));
+  assert_eq!(
+    duct::cmd!(&sarif_fmt_bin, "--src-root", &src_root)
+      .stdin_path(&sarif)
+      .full_env(&env_map)
+      .read()?,
+    output
+  );
+
+  Ok(())
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::PathBuf;
+
+#[test]
+// Test that chained uriBaseIds are resolved, and can be overridden
+fn test_uri_base_id() -> Result<()> {
+  let cargo_manifest_directory =
+    fs::canonicalize(PathBuf::from(env!("CARGO_MANIFEST_DIR")))?;
+  let cargo_workspace_directory = fs::canonicalize(PathBuf::from_iter(
+    [cargo_manifest_directory.clone(), PathBuf::from("..")].iter(),
+  ))?;
+
+  duct_sh::sh("cargo build --bin sarif-fmt")
+    .dir(cargo_workspace_directory.clone())
+    .run()?;
+
+  let sarif_fmt_bin = fs::canonicalize(PathBuf::from_iter(
+    [
+      cargo_workspace_directory.clone(),
+      PathBuf::from("./target/debug/sarif-fmt"),
+    ]
+    .iter(),
+  ))?;
+
+  let sarif = cargo_manifest_directory.join("tests/data/uri-base-id.sarif");
+
+  let mut env_map: HashMap<_, _> = std::env::vars().collect();
+  env_map.insert("NO_COLOR".into(), "1".into());
+
+  // TESTS does not exist on this machine, so the source cannot be found
+  let output = duct::cmd!(&sarif_fmt_bin, "-i", &sarif)
+    .dir(&cargo_workspace_directory)
+    .full_env(&env_map)
+    .read()?;
+  assert!(output.contains("warning: this comparison involving"));
+  assert!(!output.contains("if vec.len() <= 0 {}"));
+
+  let output = duct::cmd!(
+    &sarif_fmt_bin,
+    "--src-root",
+    "TESTS=sarif-fmt/tests",
+    "-i",
+    &sarif
+  )
+  .dir(&cargo_workspace_directory)
+  .full_env(&env_map)
+  .read()?;
+  assert!(output.contains("src/main.rs:3:6"));
+  assert!(output.contains("if vec.len() <= 0 {}"));
+
+  let output = duct::cmd!(
+    &sarif_fmt_bin,
+    "--src-root",
+    format!(
+      "TESTS={}",
+      cargo_manifest_directory.join("tests").to_str().unwrap()
+    ),
+    "-m",
+    "plain",
+    "-i",
+    &sarif
+  )
+  .dir(&cargo_workspace_directory)
+  .full_env(&env_map)
+  .read()?;
+  assert!(output.starts_with("src/main.rs:3:6: warning:"));
+
+  Ok(())
+}
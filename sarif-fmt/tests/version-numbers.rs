@@ -6,4 +6,5 @@ fn test_readme_deps() {
 #[test]
 fn test_html_root_url() {
   version_sync::assert_html_root_url_updated!("src/bin.rs");
+  version_sync::assert_html_root_url_updated!("src/lib.rs");
 }
@@ -0,0 +1,256 @@
+//! Describes the files referenced by the results of a SARIF log in
+//! `run.artifacts`.
+//!
+//! Every file referenced by an [sarif::ArtifactLocation] of a result is read
+//! from disk and described by a [sarif::Artifact] holding its sha-256 hash,
+//! length, encoding and mime type, which lets consumers check whether a result
+//! refers to the same contents as the file they have checked out. The
+//! references then point to their artifact with `ArtifactLocation.index`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_sarif::artifacts::add_artifacts;
+//! use serde_sarif::sarif::Sarif;
+//!
+//! let mut sarif: Sarif = serde_json::from_str(
+//!   r#"{ "version": "2.1.0", "runs": [{ "tool": { "driver": { "name": "clippy" } }, "results": [{
+//!     "message": { "text": "unneeded `return` statement" },
+//!     "locations": [{ "physicalLocation": { "artifactLocation": { "uri": "src/lib.rs" } } }]
+//!   }] }] }"#
+//! ).unwrap();
+//!
+//! add_artifacts(&mut sarif);
+//! let run = &sarif.runs[0];
+//! assert_eq!(run.artifacts.as_ref().unwrap().len(), 1);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::{sarif, uri, visit};
+
+/// The key of the sha-256 hash in `Artifact.hashes`
+pub const SHA_256: &str = "sha-256";
+
+// Returns the IANA name of the encoding of `contents`, if it is text
+fn encoding(contents: &[u8]) -> Option<&'static str> {
+  if contents.starts_with(&[0xff, 0xfe]) {
+    Some("utf-16le")
+  } else if contents.starts_with(&[0xfe, 0xff]) {
+    Some("utf-16be")
+  } else if !contents.contains(&0) && std::str::from_utf8(contents).is_ok() {
+    Some("utf-8")
+  } else {
+    None
+  }
+}
+
+// Returns the mime type of the file at `path`, from its name and whether it is
+// text
+fn mime_type(path: &Path, text: bool) -> &'static str {
+  let name = path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .unwrap_or_default();
+  let extension = path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .unwrap_or_default();
+  match (name, extension) {
+    ("Dockerfile", _) | (_, "dockerfile") => "text/x-dockerfile",
+    (_, "rs") => "text/x-rust",
+    (_, "sh") | (_, "bash") => "text/x-shellscript",
+    (_, "c") | (_, "h") => "text/x-c",
+    (_, "cc") | (_, "cpp") | (_, "cxx") | (_, "hh") | (_, "hpp") => {
+      "text/x-c++"
+    }
+    (_, "json") | (_, "sarif") => "application/json",
+    (_, "toml") => "application/toml",
+    (_, "yaml") | (_, "yml") => "application/yaml",
+    (_, "md") => "text/markdown",
+    _ if text => "text/plain",
+    _ => "application/octet-stream",
+  }
+}
+
+// Returns the artifact describing the file at `location`, resolved against
+// `original_uri_base_ids`, which only holds the location when the file cannot
+// be read
+fn describe(
+  location: &sarif::ArtifactLocation,
+  original_uri_base_ids: Option<&BTreeMap<String, sarif::ArtifactLocation>>,
+) -> sarif::Artifact {
+  let mut artifact = sarif::Artifact::builder()
+    .location(
+      sarif::ArtifactLocation::builder()
+        .uri(location.uri.clone().unwrap_or_default())
+        .build(),
+    )
+    .build();
+  if let Some(artifact_location) = artifact.location.as_mut() {
+    artifact_location.uri_base_id = location.uri_base_id.clone();
+  }
+
+  let path = match uri::resolve_path(location, original_uri_base_ids) {
+    Ok(path) => path,
+    Err(_) => return artifact,
+  };
+  if let Ok(contents) = fs::read(&path) {
+    let hash: String = Sha256::digest(&contents)
+      .iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect();
+    let encoding = encoding(&contents);
+    artifact.hashes = Some(BTreeMap::from([(SHA_256.to_string(), hash)]));
+    artifact.length = Some(contents.len() as i64);
+    artifact.encoding = encoding.map(String::from);
+    artifact.mime_type = Some(mime_type(&path, encoding.is_some()).to_string());
+  }
+  artifact
+}
+
+// Collects the artifacts referenced by the results of a run one result at a
+// time, so that converters which stream their results can describe them too
+pub(crate) struct ArtifactCollector {
+  original_uri_base_ids: Option<BTreeMap<String, sarif::ArtifactLocation>>,
+  artifacts: Vec<sarif::Artifact>,
+  indices: HashMap<(String, Option<String>), i64>,
+}
+
+impl ArtifactCollector {
+  // Returns a collector which appends to the existing `artifacts` of a run
+  // whose base URIs are `original_uri_base_ids`
+  pub(crate) fn new(
+    artifacts: Vec<sarif::Artifact>,
+    original_uri_base_ids: Option<BTreeMap<String, sarif::ArtifactLocation>>,
+  ) -> Self {
+    let indices = artifacts
+      .iter()
+      .enumerate()
+      .filter_map(|(index, artifact)| {
+        let location = artifact.location.as_ref()?;
+        Some((
+          (location.uri.clone()?, location.uri_base_id.clone()),
+          index as i64,
+        ))
+      })
+      .collect();
+    Self {
+      original_uri_base_ids,
+      artifacts,
+      indices,
+    }
+  }
+
+  // Sets the index of every artifact location of `result` which has a uri,
+  // describing the files which were not referenced before
+  pub(crate) fn add(&mut self, result: &mut sarif::Result) {
+    visit::for_each_artifact_location_mut(result, &mut |artifact_location| {
+      let uri = match artifact_location.uri.as_ref() {
+        Some(uri) if artifact_location.index.is_none() => uri,
+        _ => return,
+      };
+      let key = (uri.clone(), artifact_location.uri_base_id.clone());
+      let artifacts = &mut self.artifacts;
+      let original_uri_base_ids = self.original_uri_base_ids.as_ref();
+      let index = *self.indices.entry(key).or_insert_with(|| {
+        artifacts.push(describe(artifact_location, original_uri_base_ids));
+        artifacts.len() as i64 - 1
+      });
+      artifact_location.index = Some(index);
+    });
+  }
+
+  pub(crate) fn into_artifacts(self) -> Vec<sarif::Artifact> {
+    self.artifacts
+  }
+}
+
+/// Populates `run.artifacts` with every file referenced by the results of each
+/// run of `sarif`, and points every reference to its artifact
+///
+/// Files are read from the path their uri resolves to (see
+/// [crate::uri::resolve_path]), so that a log whose URIs are normalized (see
+/// [crate::uri::normalize_uris]) is read from the root of its sources. The
+/// artifact of a file holds its `sha-256` hash, `length`, `encoding` (`utf-8`,
+/// `utf-16le` or `utf-16be`, when it is text) and `mimeType`; only its location
+/// is recorded when it cannot be read. Artifact locations which already have an `index`
+/// are left untouched, and the existing artifacts of a run are kept.
+///
+/// # Arguments
+///
+/// * `sarif` - The SARIF log whose artifacts to collect
+pub fn add_artifacts(sarif: &mut sarif::Sarif) {
+  sarif.runs.iter_mut().for_each(|run| {
+    let mut collector = ArtifactCollector::new(
+      run.artifacts.take().unwrap_or_default(),
+      run.original_uri_base_ids.clone(),
+    );
+    run
+      .results
+      .iter_mut()
+      .flatten()
+      .for_each(|result| collector.add(result));
+    let artifacts = collector.into_artifacts();
+    if !artifacts.is_empty() {
+      run.artifacts = Some(artifacts);
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_add_artifacts() {
+    let mut sarif: sarif::Sarif = serde_json::from_str(
+      r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"clippy"}},"results":[
+        {"message":{"text":"a"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"Cargo.toml"}}}]},
+        {"message":{"text":"b"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"does/not/exist.rs"}}}],
+         "relatedLocations":[{"physicalLocation":{"artifactLocation":{"uri":"Cargo.toml"}}}]}
+      ]}]}"#,
+    )
+    .unwrap();
+
+    add_artifacts(&mut sarif);
+    let run = &sarif.runs[0];
+    let artifacts = run.artifacts.as_ref().unwrap();
+    assert_eq!(artifacts.len(), 2);
+
+    let contents = fs::read("Cargo.toml").unwrap();
+    assert_eq!(artifacts[0].length, Some(contents.len() as i64));
+    assert_eq!(artifacts[0].encoding.as_deref(), Some("utf-8"));
+    assert_eq!(artifacts[0].mime_type.as_deref(), Some("application/toml"));
+    assert_eq!(artifacts[0].hashes.as_ref().unwrap()[SHA_256].len(), 64);
+    assert!(artifacts[1].hashes.is_none());
+
+    let results = run.results.as_ref().unwrap();
+    let index = |location: &sarif::Location| {
+      location
+        .physical_location
+        .as_ref()
+        .and_then(|physical_location| {
+          physical_location.artifact_location.as_ref()
+        })
+        .and_then(|artifact_location| artifact_location.index)
+    };
+    assert_eq!(index(&results[0].locations.as_ref().unwrap()[0]), Some(0));
+    assert_eq!(index(&results[1].locations.as_ref().unwrap()[0]), Some(1));
+    assert_eq!(
+      index(&results[1].related_locations.as_ref().unwrap()[0]),
+      Some(0)
+    );
+  }
+
+  #[test]
+  fn test_encoding() {
+    assert_eq!(encoding(b"fn main() {}\n"), Some("utf-8"));
+    assert_eq!(encoding(&[0xff, 0xfe, b'a', 0]), Some("utf-16le"));
+    assert_eq!(encoding(&[0x7f, b'E', b'L', b'F', 0, 0]), None);
+  }
+}
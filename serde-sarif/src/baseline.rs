@@ -0,0 +1,421 @@
+//! Compares a SARIF log against a baseline log, populating the
+//! `baselineState` of every result.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_sarif::baseline::compare;
+//! use serde_sarif::sarif::{ResultBaselineState, Sarif};
+//!
+//! let baseline: Sarif = serde_json::from_str(
+//!   r#"{ "version": "2.1.0", "runs": [{ "tool": { "driver": { "name": "clippy" } }, "results": [] }] }"#
+//! ).unwrap();
+//! let current: Sarif = serde_json::from_str(
+//!   r#"{ "version": "2.1.0", "runs": [{ "tool": { "driver": { "name": "clippy" } }, "results": [{ "message": { "text": "oops" } }] }] }"#
+//! ).unwrap();
+//!
+//! let compared = compare(&baseline, current);
+//! let results = compared.runs[0].results.as_ref().unwrap();
+//! assert_eq!(results[0].baseline_state, Some(ResultBaselineState::New));
+//! ```
+
+use crate::{sarif, visit};
+use std::collections::{BTreeMap, HashMap};
+
+// A result of the baseline log, along with the run it belongs to
+struct BaselineResult<'a> {
+  run: &'a sarif::Run,
+  result: &'a sarif::Result,
+  matched: bool,
+}
+
+fn message(result: &sarif::Result) -> Option<&str> {
+  result.message.text.as_deref()
+}
+
+// Returns the uri and start line of the first location of the result
+fn primary_location(result: &sarif::Result) -> (Option<&str>, Option<i64>) {
+  result
+    .locations
+    .as_ref()
+    .and_then(|locations| locations.first())
+    .and_then(|location| location.physical_location.as_ref())
+    .map_or((None, None), |physical_location| {
+      (
+        physical_location
+          .artifact_location
+          .as_ref()
+          .and_then(|artifact_location| artifact_location.uri.as_deref()),
+        physical_location
+          .region
+          .as_ref()
+          .and_then(|region| region.start_line),
+      )
+    })
+}
+
+// Returns the `fingerprints` and `partialFingerprints` entries of the result,
+// each as whether it is a partial fingerprint, its key and its value
+fn fingerprints(
+  result: &sarif::Result,
+) -> impl Iterator<Item = (bool, &str, &str)> {
+  std::iter::once((false, result.fingerprints.as_ref()))
+    .chain(std::iter::once((
+      true,
+      result.partial_fingerprints.as_ref(),
+    )))
+    .flat_map(|(partial, fingerprints)| {
+      fingerprints
+        .into_iter()
+        .flat_map(BTreeMap::iter)
+        .map(move |(key, value)| (partial, key.as_str(), value.as_str()))
+    })
+}
+
+fn fingerprints_match(
+  current: &sarif::Result,
+  baseline: &sarif::Result,
+) -> bool {
+  fingerprints(current)
+    .any(|fingerprint| fingerprints(baseline).any(|other| other == fingerprint))
+}
+
+type Matcher = fn(&sarif::Result, &sarif::Result) -> bool;
+
+// The heuristics used to match results of the same rule which share no
+// fingerprint, from most to least precise
+const MATCHERS: [Matcher; 3] = [
+  // 2. results with the same message and location
+  |current, baseline| {
+    message(current) == message(baseline)
+      && primary_location(current) == primary_location(baseline)
+  },
+  // 3. results with the same message in the same file (lines moved)
+  |current, baseline| {
+    message(current) == message(baseline)
+      && primary_location(current).0 == primary_location(baseline).0
+  },
+  // 4. results at the same location (message reworded)
+  |current, baseline| primary_location(current) == primary_location(baseline),
+];
+
+// The results of the baseline log, indexed by the name of their tool along
+// with each of their fingerprints and with their rule id, so that a result is
+// only compared with the baseline results which may match it
+struct BaselineIndex<'a> {
+  results: Vec<BaselineResult<'a>>,
+  fingerprints: HashMap<(&'a str, bool, &'a str, &'a str), Vec<usize>>,
+  rules: HashMap<(&'a str, Option<&'a str>), Vec<usize>>,
+}
+
+impl<'a> BaselineIndex<'a> {
+  fn new(baseline: &'a sarif::Sarif) -> Self {
+    let mut index = BaselineIndex {
+      results: vec![],
+      fingerprints: HashMap::new(),
+      rules: HashMap::new(),
+    };
+    baseline.runs.iter().for_each(|run| {
+      let tool = run.tool.driver.name.as_str();
+      run.results.iter().flatten().for_each(|result| {
+        let i = index.results.len();
+        fingerprints(result).for_each(|(partial, key, value)| {
+          index
+            .fingerprints
+            .entry((tool, partial, key, value))
+            .or_default()
+            .push(i);
+        });
+        index
+          .rules
+          .entry((tool, visit::rule_id(run, result)))
+          .or_default()
+          .push(i);
+        index.results.push(BaselineResult {
+          run,
+          result,
+          matched: false,
+        });
+      });
+    });
+    index
+  }
+
+  // Marks the baseline result at `i` as matched by `result`, returning the
+  // state of `result`
+  fn match_result(
+    &mut self,
+    i: usize,
+    result: &sarif::Result,
+  ) -> sarif::ResultBaselineState {
+    let candidate = &mut self.results[i];
+    candidate.matched = true;
+    matched_state(result, candidate.result)
+  }
+}
+
+// Results matched by a fingerprint are unchanged even if they moved, as the
+// fingerprint identifies them independently of their location
+fn matched_state(
+  current: &sarif::Result,
+  baseline: &sarif::Result,
+) -> sarif::ResultBaselineState {
+  if message(current) == message(baseline)
+    && (fingerprints_match(current, baseline)
+      || primary_location(current) == primary_location(baseline))
+  {
+    sarif::ResultBaselineState::Unchanged
+  } else {
+    sarif::ResultBaselineState::Updated
+  }
+}
+
+// Prepares a baseline result to be appended to `run`: its rule is looked up
+// (or added) by id, and references into the baseline run are dropped.
+fn absent_result(
+  run: &mut sarif::Run,
+  baseline_run: &sarif::Run,
+  baseline_result: &sarif::Result,
+) -> sarif::Result {
+  let mut result = baseline_result.clone();
+  result.baseline_state = Some(sarif::ResultBaselineState::Absent);
+
+  let rule_index =
+    visit::rule_id(baseline_run, baseline_result).and_then(|id| {
+      let baseline_rule = baseline_run
+        .tool
+        .driver
+        .rules
+        .iter()
+        .flatten()
+        .find(|rule| rule.id == id)?;
+      let rules = run.tool.driver.rules.get_or_insert_with(Vec::new);
+      let index =
+        rules
+          .iter()
+          .position(|rule| rule.id == id)
+          .unwrap_or_else(|| {
+            rules.push(baseline_rule.clone());
+            rules.len() - 1
+          });
+      Some(index as i64)
+    });
+  result.rule_index = rule_index;
+  if let Some(rule) = result.rule.as_mut() {
+    if rule.tool_component.is_none() {
+      rule.index = rule_index;
+    }
+  }
+
+  visit::for_each_artifact_location_mut(
+    &mut result,
+    &mut |artifact_location| {
+      artifact_location.index = None;
+    },
+  );
+  if let Some(provenance) = result.provenance.as_mut() {
+    provenance.invocation_index = None;
+  }
+
+  result
+}
+
+/// Returns `current` with the `baselineState` of every result set relative to
+/// `baseline`
+///
+/// Runs are paired by `tool.driver.name`. Each result is matched against the
+/// not yet matched results of the baseline runs of the same tool using, from
+/// most to least precise:
+///
+/// 1. a `fingerprints` or `partialFingerprints` entry with the same key and
+///    value
+/// 2. the same rule id, message text, artifact uri and start line
+/// 3. the same rule id, message text and artifact uri
+/// 4. the same rule id, artifact uri and start line
+///
+/// Matched results are `unchanged` when their message text is equal and they
+/// share a fingerprint or their primary location is equal, and `updated`
+/// otherwise. Results without a match are
+/// `new`. Baseline results which matched no result are appended as `absent`
+/// to the first run of the same tool, or to a copy of their baseline run if
+/// the current log contains no run of that tool.
+///
+/// # Arguments
+///
+/// * `baseline` - The SARIF log of a previous analysis
+/// * `current` - The SARIF log of the current analysis
+pub fn compare(
+  baseline: &sarif::Sarif,
+  mut current: sarif::Sarif,
+) -> sarif::Sarif {
+  let mut index = BaselineIndex::new(baseline);
+
+  current.runs.iter_mut().for_each(|run| {
+    let results = run.results.as_deref().unwrap_or_default();
+    let tool = run.tool.driver.name.as_str();
+    let mut states: Vec<Option<sarif::ResultBaselineState>> =
+      vec![None; results.len()];
+    // 1. results which share a fingerprint
+    results.iter().enumerate().for_each(|(i, result)| {
+      let candidate = fingerprints(result)
+        .filter_map(|(partial, key, value)| {
+          index.fingerprints.get(&(tool, partial, key, value))
+        })
+        .flatten()
+        .copied()
+        .filter(|&candidate| !index.results[candidate].matched)
+        .min();
+      if let Some(candidate) = candidate {
+        states[i] = Some(index.match_result(candidate, result));
+      }
+    });
+    MATCHERS.iter().for_each(|matcher| {
+      results.iter().enumerate().for_each(|(i, result)| {
+        if states[i].is_some() {
+          return;
+        }
+        let candidate = index
+          .rules
+          .get(&(tool, visit::rule_id(run, result)))
+          .into_iter()
+          .flatten()
+          .copied()
+          .find(|&candidate| {
+            let candidate = &index.results[candidate];
+            !candidate.matched && matcher(result, candidate.result)
+          });
+        if let Some(candidate) = candidate {
+          states[i] = Some(index.match_result(candidate, result));
+        }
+      });
+    });
+
+    run
+      .results
+      .iter_mut()
+      .flatten()
+      .zip(states)
+      .for_each(|(result, state)| {
+        result.baseline_state =
+          Some(state.unwrap_or(sarif::ResultBaselineState::New));
+      });
+  });
+
+  index
+    .results
+    .into_iter()
+    .filter(|candidate| !candidate.matched)
+    .for_each(|candidate| {
+      let name = &candidate.run.tool.driver.name;
+      let run = match current
+        .runs
+        .iter_mut()
+        .position(|run| &run.tool.driver.name == name)
+      {
+        Some(index) => &mut current.runs[index],
+        None => {
+          let mut run = candidate.run.clone();
+          run.results = Some(vec![]);
+          current.runs.push(run);
+          current.runs.last_mut().unwrap()
+        }
+      };
+      let result = absent_result(run, candidate.run, candidate.result);
+      run.results.get_or_insert_with(Vec::new).push(result);
+    });
+
+  current
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use sarif::ResultBaselineState;
+
+  fn log(results: &str) -> sarif::Sarif {
+    serde_json::from_str(&format!(
+      r#"{{"version":"2.1.0","runs":[{{
+        "tool":{{"driver":{{"name":"clippy","rules":[{{"id":"a"}},{{"id":"b"}}]}}}},
+        "results":[{}]
+      }}]}}"#,
+      results
+    ))
+    .unwrap()
+  }
+
+  fn result(rule: &str, text: &str, uri: &str, line: i64) -> String {
+    format!(
+      r#"{{"ruleId":"{}","message":{{"text":"{}"}},"locations":[{{"physicalLocation":{{"artifactLocation":{{"uri":"{}"}},"region":{{"startLine":{}}}}}}}]}}"#,
+      rule, text, uri, line
+    )
+  }
+
+  fn states(sarif: &sarif::Sarif) -> Vec<ResultBaselineState> {
+    sarif.runs[0]
+      .results
+      .iter()
+      .flatten()
+      .map(|result| result.baseline_state.unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn test_compare_states() {
+    let baseline = log(
+      &[
+        result("a", "unchanged", "lib.rs", 1),
+        result("a", "moved", "lib.rs", 2),
+        result("b", "fixed", "lib.rs", 3),
+      ]
+      .join(","),
+    );
+    let current = log(
+      &[
+        result("a", "unchanged", "lib.rs", 1),
+        result("a", "moved", "lib.rs", 12),
+        result("b", "introduced", "main.rs", 1),
+      ]
+      .join(","),
+    );
+
+    let compared = compare(&baseline, current);
+    assert_eq!(
+      states(&compared),
+      vec![
+        ResultBaselineState::Unchanged,
+        ResultBaselineState::Updated,
+        ResultBaselineState::New,
+        ResultBaselineState::Absent,
+      ]
+    );
+    let absent = &compared.runs[0].results.as_ref().unwrap()[3];
+    assert_eq!(absent.message.text.as_deref(), Some("fixed"));
+    assert_eq!(absent.rule_index, Some(1));
+  }
+
+  #[test]
+  fn test_compare_fingerprints() {
+    let baseline = log(
+      r#"{"ruleId":"a","message":{"text":"old"},"partialFingerprints":{"hash/v1":"1234"}}"#,
+    );
+    let current = log(
+      r#"{"ruleId":"a","message":{"text":"new"},"partialFingerprints":{"hash/v1":"1234"}}"#,
+    );
+
+    let compared = compare(&baseline, current);
+    assert_eq!(states(&compared), vec![ResultBaselineState::Updated]);
+  }
+
+  #[test]
+  fn test_compare_fingerprints_moved() {
+    let baseline = log(
+      r#"{"ruleId":"a","message":{"text":"moved"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"lib.rs"},"region":{"startLine":2}}}],"partialFingerprints":{"hash/v1":"1234"}}"#,
+    );
+    let current = log(
+      r#"{"ruleId":"a","message":{"text":"moved"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"main.rs"},"region":{"startLine":12}}}],"partialFingerprints":{"hash/v1":"1234"}}"#,
+    );
+
+    let compared = compare(&baseline, current);
+    assert_eq!(states(&compared), vec![ResultBaselineState::Unchanged]);
+  }
+}
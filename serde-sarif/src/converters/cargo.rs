@@ -1,10 +1,16 @@
 use std::{
-  collections::HashMap,
+  collections::{BTreeMap, HashMap},
   convert::From,
   io::{BufWriter, Write},
 };
 
+use super::ConverterOptions;
+use crate::artifacts::ArtifactCollector;
+use crate::fingerprint::Fingerprinter;
+use crate::fix::APPLICABILITY;
 use crate::sarif::{self, Location};
+use crate::stream::SarifWriter;
+use crate::uri::UriNormalizer;
 use anyhow::Result;
 use cargo_metadata::{
   self,
@@ -40,6 +46,19 @@ impl From<&DiagnosticSpan> for sarif::Region {
   }
 }
 
+impl From<&DiagnosticSpan> for sarif::Replacement {
+  fn from(span: &DiagnosticSpan) -> Self {
+    sarif::Replacement::builder()
+      .deleted_region(sarif::Region::from(span))
+      .inserted_content(
+        sarif::ArtifactContent::builder()
+          .text(span.suggested_replacement.clone().unwrap_or_default())
+          .build(),
+      )
+      .build()
+  }
+}
+
 impl From<&DiagnosticLevel> for sarif::ResultLevel {
   fn from(level: &DiagnosticLevel) -> Self {
     match level {
@@ -89,38 +108,187 @@ fn build_global_message<W: Write>(
     .try_for_each(|diagnostic| build_global_message(diagnostic, writer))
 }
 
-/// Collects all the locations in the diagnostic's children spans
+// Matches the frames of a miri backtrace, ex. "inside `main` at
+// src/main.rs:7:5: 7:12", where the location is left out of the message when
+// the frame has a span
+const BACKTRACE_FRAME: &str = r"^inside (?:`(?P<function>.+)`|(?P<closure>closure))(?: at (?P<file>.+?):(?P<line>\d+):(?P<column>\d+)(?:: (?P<end_line>\d+):(?P<end_column>\d+))?)?$";
+
+// Returns whether `child` is the heading of a miri backtrace, ex. "BACKTRACE:"
+// or "BACKTRACE on thread `worker`:"
+fn is_backtrace_heading(child: &Diagnostic) -> bool {
+  child.message.starts_with("BACKTRACE")
+}
+
+// Returns the frame of a miri backtrace described by `child`, located at its
+// primary span if it has one, else at the location in its message
+fn get_stack_frame(
+  child: &Diagnostic,
+  frame_re: &regex::Regex,
+) -> Option<sarif::StackFrame> {
+  let captures = frame_re.captures(&child.message)?;
+  let function = captures
+    .name("function")
+    .or_else(|| captures.name("closure"))?
+    .as_str();
+  let number =
+    |name: &str| -> Option<i64> { captures.name(name)?.as_str().parse().ok() };
+
+  let span = child
+    .spans
+    .iter()
+    .find(|span| span.is_primary)
+    .or_else(|| child.spans.first());
+  let mut location = match (span, captures.name("file")) {
+    (Some(span), _) => Location::from(span),
+    (None, Some(file)) => {
+      let start_line = number("line")?;
+      let start_column = number("column")?;
+      sarif::Location::builder()
+        .physical_location(
+          sarif::PhysicalLocation::builder()
+            .artifact_location(
+              sarif::ArtifactLocation::builder()
+                .uri(file.as_str())
+                .build(),
+            )
+            .region(
+              sarif::Region::builder()
+                .start_line(start_line)
+                .start_column(start_column)
+                .end_line(number("end_line").unwrap_or(start_line))
+                .end_column(number("end_column").unwrap_or(start_column))
+                .build(),
+            )
+            .build(),
+        )
+        .build()
+    }
+    (None, None) => sarif::Location::builder().build(),
+  };
+  location.message = Some(sarif::Message::builder().text(function).build());
+  Some(sarif::StackFrame::builder().location(location).build())
+}
+
+/// Collects the backtraces in the diagnostic's children (as reported by miri),
+/// one stack per "BACKTRACE" heading, with a frame for each "inside" note that
+/// follows it
+fn get_stacks(
+  diagnostic: &Diagnostic,
+  frame_re: &regex::Regex,
+) -> Vec<sarif::Stack> {
+  let mut stacks: Vec<sarif::Stack> = vec![];
+  for child in &diagnostic.children {
+    if is_backtrace_heading(child) {
+      stacks.push(
+        sarif::Stack::builder()
+          .message(
+            sarif::Message::builder()
+              .text(child.message.trim_end_matches(':'))
+              .build(),
+          )
+          .frames(vec![])
+          .build(),
+      );
+    } else if let Some(frame) = get_stack_frame(child, frame_re) {
+      match stacks.last_mut() {
+        Some(stack) => stack.frames.push(frame),
+        None => {
+          stacks.push(sarif::Stack::builder().frames(vec![frame]).build())
+        }
+      }
+    }
+  }
+  stacks.retain(|stack| !stack.frames.is_empty());
+  stacks
+}
+
+/// Collects all the locations in the diagnostic's children spans, except for
+/// the frames of backtraces, which are collected by [get_stacks]
 fn get_related_locations(
   diagnostic: &Diagnostic,
+  frame_re: &regex::Regex,
 ) -> Result<Vec<Location>, anyhow::Error> {
   let mut related_locations = vec![];
-  for child in &diagnostic.children {
-    let mut message = child.message.clone();
+  for child in diagnostic.children.iter().filter(|child| {
+    !is_backtrace_heading(child) && !frame_re.is_match(&child.message)
+  }) {
     for child_span in &child.spans {
       let mut child_loc: Location = child_span.into();
-      if child_span.suggested_replacement.is_some() {
-        let replacement = child_span.suggested_replacement.as_ref().unwrap();
-        message.push_str(&format!(" \"{replacement}\""));
-      }
-
-      child_loc.message = Some(sarif::Message::from(&message));
+      child_loc.message = Some(sarif::Message::from(&child.message));
       related_locations.push(child_loc);
     }
   }
   Ok(related_locations)
 }
 
-pub(crate) fn process<I: Iterator<Item = Diagnostic>>(
+/// Collects the suggestions of the diagnostic's children, one fix per child
+fn get_fixes(diagnostic: &Diagnostic) -> Vec<sarif::Fix> {
+  diagnostic
+    .children
+    .iter()
+    .filter_map(|child| {
+      let spans: Vec<&DiagnosticSpan> = child
+        .spans
+        .iter()
+        .filter(|span| span.suggested_replacement.is_some())
+        .collect();
+      let applicability = spans.first()?.suggestion_applicability.as_ref();
+
+      let mut artifact_changes: Vec<sarif::ArtifactChange> = vec![];
+      spans.iter().for_each(|span| {
+        let replacement = sarif::Replacement::from(*span);
+        match artifact_changes.iter_mut().find(|artifact_change| {
+          artifact_change.artifact_location.uri.as_ref()
+            == Some(&span.file_name)
+        }) {
+          Some(artifact_change) => {
+            artifact_change.replacements.push(replacement)
+          }
+          None => artifact_changes.push(
+            sarif::ArtifactChange::builder()
+              .artifact_location(sarif::ArtifactLocation::from(*span))
+              .replacements(vec![replacement])
+              .build(),
+          ),
+        }
+      });
+
+      let fix = sarif::Fix::builder()
+        .description(&child.message)
+        .artifact_changes(artifact_changes);
+      Some(match applicability.map(serde_json::to_value) {
+        Some(Ok(applicability)) => fix
+          .properties(
+            sarif::PropertyBag::builder()
+              .additional_properties(BTreeMap::from([(
+                APPLICABILITY.to_string(),
+                applicability,
+              )]))
+              .build(),
+          )
+          .build(),
+        _ => fix.build(),
+      })
+    })
+    .collect()
+}
+
+// Converts every diagnostic of `diagnostic_iter` into results, which are
+// passed to `f` as they are produced, and returns the rules they refer to
+fn for_each_result<I, F>(
   mut diagnostic_iter: I,
-  tool_name: &str,
-  tool_info_uri: &str,
-) -> Result<sarif::Sarif> {
-  let mut results = vec![];
+  mut f: F,
+) -> Result<Vec<sarif::ReportingDescriptor>>
+where
+  I: Iterator<Item = Diagnostic>,
+  F: FnMut(sarif::Result) -> Result<()>,
+{
   let mut map = HashMap::new();
   let mut rules = vec![];
 
   let re =
     regex::Regex::new(r"^for further information visit (?P<url>\S+)").unwrap();
+  let frame_re = regex::Regex::new(BACKTRACE_FRAME).unwrap();
 
   diagnostic_iter.try_for_each(|diagnostic| -> Result<()> {
     diagnostic.spans.iter().try_for_each(|span| -> Result<()> {
@@ -160,16 +328,23 @@ pub(crate) fn process<I: Iterator<Item = Diagnostic>>(
 
       if let Some(value) = map.get(&diagnostic_code) {
         let level: sarif::ResultLevel = (&diagnostic.level).into();
-        results.push(
-          sarif::Result::builder()
-            .rule_id(diagnostic_code)
-            .rule_index(*value)
-            .message(&diagnostic)
-            .locations(vec![span.into()])
-            .level(level)
-            .related_locations(get_related_locations(&diagnostic)?)
-            .build(),
-        );
+        let mut result = sarif::Result::builder()
+          .rule_id(diagnostic_code)
+          .rule_index(*value)
+          .message(&diagnostic)
+          .locations(vec![span.into()])
+          .level(level)
+          .related_locations(get_related_locations(&diagnostic, &frame_re)?)
+          .build();
+        let stacks = get_stacks(&diagnostic, &frame_re);
+        if !stacks.is_empty() {
+          result.stacks = Some(stacks);
+        }
+        let fixes = get_fixes(&diagnostic);
+        if !fixes.is_empty() {
+          result.fixes = Some(fixes);
+        }
+        f(result)?;
       }
       Ok(())
     })?;
@@ -177,14 +352,36 @@ pub(crate) fn process<I: Iterator<Item = Diagnostic>>(
     Ok(())
   })?;
 
-  let tool_component: sarif::ToolComponent = sarif::ToolComponent::builder()
+  Ok(rules)
+}
+
+fn tool(
+  tool_name: &str,
+  tool_info_uri: &str,
+  rules: Vec<sarif::ReportingDescriptor>,
+) -> sarif::ToolComponent {
+  sarif::ToolComponent::builder()
     .name(tool_name)
     .information_uri(tool_info_uri)
     .rules(rules)
-    .build();
+    .build()
+}
+
+pub(crate) fn process<I: Iterator<Item = Diagnostic>>(
+  diagnostic_iter: I,
+  tool_name: &str,
+  tool_info_uri: &str,
+) -> Result<sarif::Sarif> {
+  let mut results = vec![];
+  let rules = for_each_result(diagnostic_iter, |result| {
+    results.push(result);
+    Ok(())
+  })?;
+
   let run = sarif::Run::builder()
-    .tool(tool_component)
+    .tool(tool(tool_name, tool_info_uri, rules))
     .results(results)
+    .column_kind(sarif::ResultColumnKind::UnicodeCodePoints.to_string())
     .build();
 
   let sarif = sarif::Sarif::builder()
@@ -195,3 +392,126 @@ pub(crate) fn process<I: Iterator<Item = Diagnostic>>(
 
   Ok(sarif)
 }
+
+// Writes the log converted from `diagnostic_iter` to `writer` as results are
+// produced, instead of collecting them into a [sarif::Sarif] first
+pub(crate) fn process_to_writer<I: Iterator<Item = Diagnostic>, W: Write>(
+  diagnostic_iter: I,
+  tool_name: &str,
+  tool_info_uri: &str,
+  writer: W,
+  options: &ConverterOptions,
+) -> Result<()> {
+  let mut sarif_writer = SarifWriter::new(writer, tempfile::tempfile()?)?;
+  // results are normalized first, so that fingerprints and artifacts refer to
+  // the files relative to the root of the sources
+  let mut normalizer = options.src_root.as_deref().map(UriNormalizer::new);
+  let base_ids = normalizer
+    .as_ref()
+    .map(UriNormalizer::original_uri_base_ids);
+  let mut fingerprinter = options
+    .fingerprints
+    .then(|| Fingerprinter::new(base_ids.clone()));
+  let mut collector = options
+    .artifacts
+    .then(|| ArtifactCollector::new(Vec::new(), base_ids));
+  let rules = for_each_result(diagnostic_iter, |mut result| {
+    if let Some(normalizer) = normalizer.as_mut() {
+      normalizer.normalize_result(&mut result);
+    }
+    if let Some(fingerprinter) = fingerprinter.as_mut() {
+      let rule_id = result.rule_id.clone();
+      fingerprinter.add(rule_id.as_deref(), &mut result);
+    }
+    if let Some(collector) = collector.as_mut() {
+      collector.add(&mut result);
+    }
+    sarif_writer.write_result(&result)?;
+    Ok(())
+  })?;
+
+  let mut run = sarif::Run::builder()
+    .tool(tool(tool_name, tool_info_uri, rules))
+    .column_kind(sarif::ResultColumnKind::UnicodeCodePoints.to_string())
+    .build();
+  run.artifacts = collector
+    .map(ArtifactCollector::into_artifacts)
+    .filter(|artifacts| !artifacts.is_empty());
+  if let Some(normalizer) = normalizer {
+    normalizer.finish(&mut run);
+  }
+  sarif_writer.finish(&run)?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn span(line: usize, column_start: usize, column_end: usize) -> String {
+    format!(
+      r#"{{"file_name":"src/main.rs","byte_start":0,"byte_end":0,"line_start":{line},"line_end":{line},"column_start":{column_start},"column_end":{column_end},"is_primary":true,"text":[],"label":null,"suggested_replacement":null,"suggestion_applicability":null,"expansion":null}}"#
+    )
+  }
+
+  fn note(message: &str, spans: &[String]) -> String {
+    format!(
+      r#"{{"message":"{}","code":null,"level":"note","spans":[{}],"children":[],"rendered":null}}"#,
+      message,
+      spans.join(",")
+    )
+  }
+
+  #[test]
+  fn test_miri_backtrace_stacks() {
+    let json = format!(
+      r#"{{"message":"Undefined Behavior: null pointer dereference","code":null,"level":"error","spans":[{}],"children":[{},{},{},{},{}],"rendered":null}}"#,
+      span(3, 5, 20),
+      note("this indicates a bug in the program", &[]),
+      note("BACKTRACE:", &[]),
+      note("inside `deref` at src/main.rs:3:5: 3:20", &[]),
+      note("inside closure", &[span(7, 13, 21)]),
+      note("inside `main`", &[span(8, 5, 13)]),
+    );
+    let diagnostic: Diagnostic = serde_json::from_str(&json).unwrap();
+    let sarif = process(std::iter::once(diagnostic), "miri", "").unwrap();
+    let result = &sarif.runs[0].results.as_ref().unwrap()[0];
+
+    assert_eq!(result.related_locations, Some(vec![]));
+    let stacks = result.stacks.as_ref().unwrap();
+    assert_eq!(stacks.len(), 1);
+    assert_eq!(
+      stacks[0].message.as_ref().unwrap().text.as_deref(),
+      Some("BACKTRACE")
+    );
+    let frames: Vec<(&str, i64, i64, i64)> = stacks[0]
+      .frames
+      .iter()
+      .map(|frame| {
+        let location = frame.location.as_ref().unwrap();
+        let region = location
+          .physical_location
+          .as_ref()
+          .unwrap()
+          .region
+          .as_ref()
+          .unwrap();
+        (
+          location.message.as_ref().unwrap().text.as_deref().unwrap(),
+          region.start_line.unwrap(),
+          region.start_column.unwrap(),
+          region.end_column.unwrap(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      frames,
+      vec![
+        ("deref", 3, 5, 20),
+        ("closure", 7, 13, 21),
+        ("main", 8, 5, 13)
+      ]
+    );
+  }
+}
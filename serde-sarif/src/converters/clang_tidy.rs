@@ -1,3 +1,5 @@
+use super::columns::{self, ToolColumns};
+use super::{Converter, ConverterOptions};
 use crate::sarif::{self};
 use anyhow::Result;
 use once_cell::sync::Lazy;
@@ -111,6 +113,42 @@ fn parse_clang_tidy_line(
   None
 }
 
+const NAME: &str = "clang-tidy";
+const INFORMATION_URI: &str = "https://clang.llvm.org/extra/clang-tidy/";
+
+/// Converts `clang-tidy` diagnostics into SARIF
+#[derive(Clone, Debug, Default)]
+pub struct ClangTidyConverter {
+  options: ConverterOptions,
+}
+
+impl ClangTidyConverter {
+  /// Returns a converter which converts as requested by `options`
+  pub fn new(options: ConverterOptions) -> Self {
+    Self { options }
+  }
+}
+
+impl Converter for ClangTidyConverter {
+  fn name(&self) -> &'static str {
+    NAME
+  }
+
+  fn information_uri(&self) -> &'static str {
+    INFORMATION_URI
+  }
+
+  fn options(&self) -> &ConverterOptions {
+    &self.options
+  }
+
+  fn convert(&self, reader: &mut dyn BufRead) -> Result<sarif::Sarif> {
+    let mut sarif = process(reader)?;
+    self.options.apply(&mut sarif);
+    Ok(sarif)
+  }
+}
+
 fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
   let mut results = vec![];
   // Create an iterator over all the ClangTidyResult items
@@ -156,17 +194,21 @@ fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
     results.push(result);
   }
 
-  let tool_component: sarif::ToolComponent =
-    sarif::ToolComponent::builder().name("clang-tidy").build();
+  let tool_component: sarif::ToolComponent = sarif::ToolComponent::builder()
+    .name(NAME)
+    .information_uri(INFORMATION_URI)
+    .build();
   let run = sarif::Run::builder()
     .tool(tool_component)
     .results(results)
     .build();
 
-  let sarif = sarif::Sarif::builder()
+  let mut sarif = sarif::Sarif::builder()
     .version(sarif::Version::V2_1_0.to_string())
     .runs(vec![run])
     .build();
+  // clang reports the byte offset of a column in its line
+  columns::to_code_points(&mut sarif, ToolColumns::Bytes);
 
   Ok(sarif)
 }
@@ -178,12 +220,10 @@ fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
 /// * `reader` - A `BufRead` of cargo output
 /// * `writer` - A `Writer` to write the results to
 pub fn parse_to_writer<R: BufRead, W: Write>(
-  reader: R,
-  writer: W,
+  mut reader: R,
+  mut writer: W,
 ) -> Result<()> {
-  let sarif = process(reader)?;
-  serde_json::to_writer_pretty(writer, &sarif)?;
-  Ok(())
+  ClangTidyConverter::default().convert_to_writer(&mut reader, &mut writer)
 }
 
 /// Returns [sarif::Sarif] serialized into a JSON string
@@ -191,8 +231,6 @@ pub fn parse_to_writer<R: BufRead, W: Write>(
 /// # Arguments
 ///
 /// * `reader` - A `BufRead` of clang-tidy output
-pub fn parse_to_string<R: BufRead>(reader: R) -> Result<String> {
-  let sarif = process(reader)?;
-  let json = serde_json::to_string_pretty(&sarif)?;
-  Ok(json)
+pub fn parse_to_string<R: BufRead>(mut reader: R) -> Result<String> {
+  ClangTidyConverter::default().convert_to_string(&mut reader)
 }
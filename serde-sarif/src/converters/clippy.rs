@@ -1,22 +1,72 @@
 use std::io::{BufRead, Write};
 
+use super::{Converter, ConverterOptions};
 use crate::sarif;
 use anyhow::Result;
-use cargo_metadata::{self, Message};
+use cargo_metadata::{self, diagnostic::Diagnostic, Message};
 
-fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
-  let iter = Message::parse_stream(reader)
+const NAME: &str = "clippy";
+const INFORMATION_URI: &str = "https://rust-lang.github.io/rust-clippy/";
+
+/// Converts `cargo clippy --message-format=json` output into SARIF
+#[derive(Clone, Debug, Default)]
+pub struct ClippyConverter {
+  options: ConverterOptions,
+}
+
+impl ClippyConverter {
+  /// Returns a converter which converts as requested by `options`
+  pub fn new(options: ConverterOptions) -> Self {
+    Self { options }
+  }
+}
+
+impl Converter for ClippyConverter {
+  fn name(&self) -> &'static str {
+    NAME
+  }
+
+  fn information_uri(&self) -> &'static str {
+    INFORMATION_URI
+  }
+
+  fn options(&self) -> &ConverterOptions {
+    &self.options
+  }
+
+  fn convert(&self, reader: &mut dyn BufRead) -> Result<sarif::Sarif> {
+    let mut sarif = process(reader)?;
+    self.options.apply(&mut sarif);
+    Ok(sarif)
+  }
+
+  fn convert_to_writer(
+    &self,
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+  ) -> Result<()> {
+    super::cargo::process_to_writer(
+      diagnostics(reader),
+      NAME,
+      INFORMATION_URI,
+      writer,
+      &self.options,
+    )
+  }
+}
+
+// Returns the compiler diagnostics of cargo output, read as they are needed
+fn diagnostics<R: BufRead>(reader: R) -> impl Iterator<Item = Diagnostic> {
+  Message::parse_stream(reader)
     .filter_map(|r| r.ok())
     .filter_map(|m| match m {
       Message::CompilerMessage(msg) => Some(msg.message),
       _ => None,
-    });
+    })
+}
 
-  super::cargo::process(
-    iter,
-    "clippy",
-    "https://rust-lang.github.io/rust-clippy/",
-  )
+fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
+  super::cargo::process(diagnostics(reader), NAME, INFORMATION_URI)
 }
 
 /// Returns [sarif::Sarif] serialized into a JSON stream
@@ -26,12 +76,10 @@ fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
 /// * `reader` - A `BufRead` of cargo output
 /// * `writer` - A `Writer` to write the results to
 pub fn parse_to_writer<R: BufRead, W: Write>(
-  reader: R,
-  writer: W,
+  mut reader: R,
+  mut writer: W,
 ) -> Result<()> {
-  let sarif = process(reader)?;
-  serde_json::to_writer_pretty(writer, &sarif)?;
-  Ok(())
+  ClippyConverter::default().convert_to_writer(&mut reader, &mut writer)
 }
 
 /// Returns [sarif::Sarif] serialized into a JSON string
@@ -39,8 +87,6 @@ pub fn parse_to_writer<R: BufRead, W: Write>(
 /// # Arguments
 ///
 /// * `reader` - A `BufRead` of cargo clippy output
-pub fn parse_to_string<R: BufRead>(reader: R) -> Result<String> {
-  let sarif = process(reader)?;
-  let json = serde_json::to_string_pretty(&sarif)?;
-  Ok(json)
+pub fn parse_to_string<R: BufRead>(mut reader: R) -> Result<String> {
+  ClippyConverter::default().convert_to_string(&mut reader)
 }
@@ -0,0 +1,220 @@
+// Converts the columns reported by tools which do not count them in one of
+// the column kinds of SARIF into Unicode code points, by reading the lines of
+// the files they refer to.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+
+use crate::sarif;
+use crate::uri;
+
+// The unit in which a tool counts the columns of a line
+#[derive(Clone, Copy)]
+pub(crate) enum ToolColumns {
+  // UTF-8 bytes (ex. clang)
+  #[cfg(feature = "clang-tidy-converters")]
+  Bytes,
+  // characters, with tabs advancing to the next multiple of the tab width
+  // (ex. shellcheck)
+  #[cfg(feature = "shellcheck-converters")]
+  TabStops(i64),
+}
+
+impl ToolColumns {
+  // Returns the number of columns `c` spans when it starts at `column`, which
+  // only tab stops depend on
+  #[cfg_attr(not(feature = "shellcheck-converters"), allow(unused_variables))]
+  fn width(self, c: char, column: i64) -> i64 {
+    match self {
+      #[cfg(feature = "clang-tidy-converters")]
+      ToolColumns::Bytes => c.len_utf8() as i64,
+      #[cfg(feature = "shellcheck-converters")]
+      ToolColumns::TabStops(width) if c == '\t' => width - (column - 1) % width,
+      #[cfg(feature = "shellcheck-converters")]
+      ToolColumns::TabStops(_) => 1,
+    }
+  }
+}
+
+// Returns the code point column of the tool `column` of `line`. A column in
+// the middle of a character is the column of that character, and columns past
+// the end of the line are offset from its end.
+fn code_point_column(line: &str, column: i64, unit: ToolColumns) -> i64 {
+  let mut tool_column = 1;
+  let mut code_point = 1;
+  for c in line.chars() {
+    let width = unit.width(c, tool_column);
+    if column < tool_column + width {
+      return code_point;
+    }
+    tool_column += width;
+    code_point += 1;
+  }
+  code_point + (column - tool_column).max(0)
+}
+
+// Converts columns one region at a time, reading every file once and keeping
+// track of whether any column could not be converted
+struct ColumnConverter {
+  unit: ToolColumns,
+  lines: HashMap<String, Option<Vec<String>>>,
+  unconverted: bool,
+}
+
+impl ColumnConverter {
+  fn new(unit: ToolColumns) -> Self {
+    Self {
+      unit,
+      lines: HashMap::new(),
+      unconverted: false,
+    }
+  }
+
+  // Returns the code point column of the tool `column` of `line` in the file
+  // at `uri`, or `column` itself if the line cannot be read
+  fn column(&mut self, uri: &str, line: i64, column: i64) -> i64 {
+    let unit = self.unit;
+    let lines = self.lines.entry(uri.to_string()).or_insert_with(|| {
+      fs::read_to_string(uri::to_path(uri))
+        .ok()
+        .map(|contents| contents.lines().map(String::from).collect())
+    });
+    let converted = lines
+      .as_ref()
+      .and_then(|lines| lines.get(usize::try_from(line - 1).ok()?))
+      .map(|text| code_point_column(text, column, unit));
+    self.unconverted |= converted.is_none();
+    converted.unwrap_or(column)
+  }
+
+  // Converts the columns of `region` of the file at `uri`
+  fn convert_region(&mut self, uri: &str, region: &mut sarif::Region) {
+    let start_line = match region.start_line {
+      Some(start_line) => start_line,
+      None => return,
+    };
+    if let Some(start_column) = region.start_column {
+      region.start_column = Some(self.column(uri, start_line, start_column));
+    }
+    if let Some(end_column) = region.end_column {
+      let end_line = region.end_line.unwrap_or(start_line);
+      region.end_column = Some(self.column(uri, end_line, end_column));
+    }
+  }
+
+  // Converts the columns of the regions of the locations and fixes of `result`
+  fn convert_result(&mut self, result: &mut sarif::Result) {
+    crate::visit::for_each_location_mut(result, &mut |location| {
+      let physical_location = match location.physical_location.as_mut() {
+        Some(physical_location) => physical_location,
+        None => return,
+      };
+      let uri = match physical_location
+        .artifact_location
+        .as_ref()
+        .and_then(|artifact_location| artifact_location.uri.clone())
+      {
+        Some(uri) => uri,
+        None => return,
+      };
+      physical_location
+        .region
+        .iter_mut()
+        .chain(physical_location.context_region.iter_mut())
+        .for_each(|region| self.convert_region(&uri, region));
+    });
+    result
+      .fixes
+      .iter_mut()
+      .flatten()
+      .flat_map(|fix| fix.artifact_changes.iter_mut())
+      .for_each(|artifact_change| {
+        if let Some(uri) = artifact_change.artifact_location.uri.as_deref() {
+          artifact_change
+            .replacements
+            .iter_mut()
+            .for_each(|replacement| {
+              self.convert_region(uri, &mut replacement.deleted_region)
+            });
+        }
+      });
+  }
+}
+
+// Converts the columns of every result of `sarif`, counted in `unit`, into
+// Unicode code points, and declares them in the `columnKind` of every run
+// whose columns could all be converted. The columns of a run which refers to
+// files that cannot be read are left in the unit of the tool, which no column
+// kind describes.
+pub(crate) fn to_code_points(sarif: &mut sarif::Sarif, unit: ToolColumns) {
+  let mut converter = ColumnConverter::new(unit);
+  sarif.runs.iter_mut().for_each(|run| {
+    converter.unconverted = false;
+    run
+      .results
+      .iter_mut()
+      .flatten()
+      .for_each(|result| converter.convert_result(result));
+    if !converter.unconverted {
+      run.column_kind = Some(
+        sarif::ResultColumnKind::UnicodeCodePoints
+          .to_string()
+          .into(),
+      );
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  #[cfg(feature = "clang-tidy-converters")]
+  fn test_code_point_column_bytes() {
+    // "é" is two bytes
+    assert_eq!(code_point_column("café = 1;", 6, ToolColumns::Bytes), 5);
+    assert_eq!(code_point_column("café = 1;", 5, ToolColumns::Bytes), 4);
+    assert_eq!(code_point_column("café", 6, ToolColumns::Bytes), 5);
+    assert_eq!(code_point_column("café", 8, ToolColumns::Bytes), 7);
+  }
+
+  #[test]
+  #[cfg(feature = "shellcheck-converters")]
+  fn test_code_point_column_tab_stops() {
+    // tabs advance to the next multiple of 8
+    let tabs = ToolColumns::TabStops(8);
+    assert_eq!(code_point_column("\techo $a", 9, tabs), 2);
+    assert_eq!(code_point_column("  \techo $a", 9, tabs), 4);
+    assert_eq!(code_point_column("\t\t$a", 17, tabs), 3);
+    assert_eq!(code_point_column("echo $a", 6, tabs), 6);
+  }
+
+  #[test]
+  #[cfg(feature = "clang-tidy-converters")]
+  fn test_to_code_points() {
+    let log = |uri: &str| -> sarif::Sarif {
+      serde_json::from_str(&format!(
+        r#"{{"version":"2.1.0","runs":[{{"tool":{{"driver":{{"name":"clang-tidy"}}}},"results":[{{"message":{{"text":"m"}},"locations":[{{"physicalLocation":{{"artifactLocation":{{"uri":"{}"}},"region":{{"startLine":1,"startColumn":2}}}}}}]}}]}}]}}"#,
+        uri
+      ))
+      .unwrap()
+    };
+
+    let mut sarif = log(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+    to_code_points(&mut sarif, ToolColumns::Bytes);
+    assert_eq!(
+      sarif.runs[0].column_kind,
+      Some(
+        sarif::ResultColumnKind::UnicodeCodePoints
+          .to_string()
+          .into()
+      )
+    );
+
+    let mut sarif = log("does/not/exist.c");
+    to_code_points(&mut sarif, ToolColumns::Bytes);
+    assert_eq!(sarif.runs[0].column_kind, None);
+  }
+}
@@ -8,6 +8,7 @@ use strum_macros::Display;
 use strum_macros::EnumString;
 use typed_builder::TypedBuilder;
 
+use super::{Converter, ConverterOptions};
 use crate::sarif::{self, ResultLevel};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -78,14 +79,48 @@ impl From<&HadolintResult> for sarif::Region {
   }
 }
 
-fn process<R: BufRead>(mut reader: R) -> Result<sarif::Sarif> {
-  let mut data = String::new();
-  reader.read_to_string(&mut data)?;
+const NAME: &str = "hadolint";
+const INFORMATION_URI: &str = "https://github.com/hadolint/hadolint";
+
+/// Converts `hadolint -f json` output into SARIF
+#[derive(Clone, Debug, Default)]
+pub struct HadolintConverter {
+  options: ConverterOptions,
+}
+
+impl HadolintConverter {
+  /// Returns a converter which converts as requested by `options`
+  pub fn new(options: ConverterOptions) -> Self {
+    Self { options }
+  }
+}
+
+impl Converter for HadolintConverter {
+  fn name(&self) -> &'static str {
+    NAME
+  }
+
+  fn information_uri(&self) -> &'static str {
+    INFORMATION_URI
+  }
+
+  fn options(&self) -> &ConverterOptions {
+    &self.options
+  }
+
+  fn convert(&self, reader: &mut dyn BufRead) -> Result<sarif::Sarif> {
+    let mut sarif = process(reader)?;
+    self.options.apply(&mut sarif);
+    Ok(sarif)
+  }
+}
+
+fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
   let mut results = vec![];
   let mut map = HashMap::new();
   let mut rules = vec![];
 
-  let hadolint_results: Vec<HadolintResult> = serde_json::from_str(&data)?;
+  let hadolint_results: Vec<HadolintResult> = serde_json::from_reader(reader)?;
   hadolint_results
     .iter()
     .try_for_each(|result| -> Result<()> {
@@ -119,12 +154,14 @@ fn process<R: BufRead>(mut reader: R) -> Result<sarif::Sarif> {
       Ok(())
     })?;
   let tool_component = sarif::ToolComponent::builder()
-    .name("hadolint")
+    .name(NAME)
+    .information_uri(INFORMATION_URI)
     .rules(rules)
     .build();
   let run = sarif::Run::builder()
     .tool(tool_component)
     .results(results)
+    .column_kind(sarif::ResultColumnKind::UnicodeCodePoints.to_string())
     .build();
 
   Ok(
@@ -142,12 +179,10 @@ fn process<R: BufRead>(mut reader: R) -> Result<sarif::Sarif> {
 /// * `reader` - A `BufRead` of cargo output
 /// * `writer` - A `Writer` to write the results to
 pub fn parse_to_writer<R: BufRead, W: Write>(
-  reader: R,
-  writer: W,
+  mut reader: R,
+  mut writer: W,
 ) -> Result<()> {
-  let sarif = process(reader)?;
-  serde_json::to_writer_pretty(writer, &sarif)?;
-  Ok(())
+  HadolintConverter::default().convert_to_writer(&mut reader, &mut writer)
 }
 
 /// Returns [sarif::Sarif] serialized into a JSON string
@@ -155,8 +190,6 @@ pub fn parse_to_writer<R: BufRead, W: Write>(
 /// # Arguments
 ///
 /// * `reader` - A `BufRead` of hadolint output
-pub fn parse_to_string<R: BufRead>(reader: R) -> Result<String> {
-  let sarif = process(reader)?;
-  let json = serde_json::to_string_pretty(&sarif)?;
-  Ok(json)
+pub fn parse_to_string<R: BufRead>(mut reader: R) -> Result<String> {
+  HadolintConverter::default().convert_to_string(&mut reader)
 }
@@ -1,5 +1,6 @@
 use std::io::{BufRead, Write};
 
+use super::{Converter, ConverterOptions};
 use crate::sarif;
 use anyhow::Result;
 use cargo_metadata::{self, diagnostic::Diagnostic};
@@ -33,11 +34,64 @@ impl<R: BufRead> Iterator for DiagnosticIter<R> {
   }
 }
 
-fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
+const NAME: &str = "miri";
+const INFORMATION_URI: &str = "https://github.com/rust-lang/miri";
+
+/// Converts `cargo miri` JSON diagnostics into SARIF
+#[derive(Clone, Debug, Default)]
+pub struct MiriConverter {
+  options: ConverterOptions,
+}
+
+impl MiriConverter {
+  /// Returns a converter which converts as requested by `options`
+  pub fn new(options: ConverterOptions) -> Self {
+    Self { options }
+  }
+}
+
+impl Converter for MiriConverter {
+  fn name(&self) -> &'static str {
+    NAME
+  }
+
+  fn information_uri(&self) -> &'static str {
+    INFORMATION_URI
+  }
+
+  fn options(&self) -> &ConverterOptions {
+    &self.options
+  }
+
+  fn convert(&self, reader: &mut dyn BufRead) -> Result<sarif::Sarif> {
+    let mut sarif = process(reader)?;
+    self.options.apply(&mut sarif);
+    Ok(sarif)
+  }
+
+  fn convert_to_writer(
+    &self,
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+  ) -> Result<()> {
+    super::cargo::process_to_writer(
+      diagnostics(reader),
+      NAME,
+      INFORMATION_URI,
+      writer,
+      &self.options,
+    )
+  }
+}
+
+// Returns the diagnostics of cargo miri output, read as they are needed
+fn diagnostics<R: BufRead>(reader: R) -> impl Iterator<Item = Diagnostic> {
   let stream = DiagnosticIter { input: reader };
-  let iter = stream.filter_map(|r| r.ok()).flatten();
+  stream.filter_map(|r| r.ok()).flatten()
+}
 
-  super::cargo::process(iter, "miri", "https://github.com/rust-lang/miri")
+fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
+  super::cargo::process(diagnostics(reader), NAME, INFORMATION_URI)
 }
 
 /// Returns [sarif::Sarif] serialized into a JSON stream
@@ -47,12 +101,10 @@ fn process<R: BufRead>(reader: R) -> Result<sarif::Sarif> {
 /// * `reader` - A `BufRead` of cargo miri output
 /// * `writer` - A `Writer` to write the results to
 pub fn parse_to_writer<R: BufRead, W: Write>(
-  reader: R,
-  writer: W,
+  mut reader: R,
+  mut writer: W,
 ) -> Result<()> {
-  let sarif = process(reader)?;
-  serde_json::to_writer_pretty(writer, &sarif)?;
-  Ok(())
+  MiriConverter::default().convert_to_writer(&mut reader, &mut writer)
 }
 
 /// Returns [sarif::Sarif] serialized into a JSON string
@@ -60,8 +112,6 @@ pub fn parse_to_writer<R: BufRead, W: Write>(
 /// # Arguments
 ///
 /// * `reader` - A `BufRead` of cargo miri output
-pub fn parse_to_string<R: BufRead>(reader: R) -> Result<String> {
-  let sarif = process(reader)?;
-  let json = serde_json::to_string_pretty(&sarif)?;
-  Ok(json)
+pub fn parse_to_string<R: BufRead>(mut reader: R) -> Result<String> {
+  MiriConverter::default().convert_to_string(&mut reader)
 }
@@ -1,22 +1,202 @@
 #[cfg(any(feature = "clippy-converters", feature = "miri-converters"))]
 mod cargo;
 
+#[cfg(any(
+  feature = "shellcheck-converters",
+  feature = "clang-tidy-converters"
+))]
+mod columns;
+
 #[cfg(feature = "clippy-converters")]
-#[cfg_attr(doc, doc(cfg(feature = "clippy-converters")))]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "clippy-converters")))]
 pub mod clippy;
 
 #[cfg(feature = "miri-converters")]
-#[cfg_attr(doc, doc(cfg(feature = "miri-converters")))]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "miri-converters")))]
 pub mod miri;
 
 #[cfg(feature = "hadolint-converters")]
-#[cfg_attr(doc, doc(cfg(feature = "hadolint-converters")))]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "hadolint-converters")))]
 pub mod hadolint;
 
 #[cfg(feature = "shellcheck-converters")]
-#[cfg_attr(doc, doc(cfg(feature = "shellcheck-converters")))]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "shellcheck-converters")))]
 pub mod shellcheck;
 
 #[cfg(feature = "clang-tidy-converters")]
-#[cfg_attr(doc, doc(cfg(feature = "clang-tidy-converters")))]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "clang-tidy-converters")))]
 pub mod clang_tidy;
+
+#[cfg(feature = "converters")]
+use typed_builder::TypedBuilder;
+
+/// Options shared by every converter
+///
+/// Converters which take additional options (ex. `shellcheck`) embed these in
+/// their own options type.
+#[cfg(feature = "converters")]
+#[derive(Clone, Debug, Default, TypedBuilder)]
+#[builder(field_defaults(setter(into)))]
+pub struct ConverterOptions {
+  /// Populate the `partialFingerprints` of every result, see
+  /// [add_partial_fingerprints](crate::fingerprint::add_partial_fingerprints)
+  #[builder(default)]
+  pub fingerprints: bool,
+  /// Populate `run.artifacts` with the files referenced by results, see
+  /// [add_artifacts](crate::artifacts::add_artifacts)
+  #[builder(default)]
+  pub artifacts: bool,
+  /// Make artifact URIs relative to this root of the sources, see
+  /// [normalize_uris](crate::uri::normalize_uris)
+  #[builder(default)]
+  pub src_root: Option<std::path::PathBuf>,
+}
+
+#[cfg(feature = "converters")]
+impl ConverterOptions {
+  /// Post-processes a converted log as requested by the options
+  ///
+  /// URIs are normalized first, so that fingerprints hash the uri relative to
+  /// the root of the sources and files are read from under it.
+  ///
+  /// # Arguments
+  ///
+  /// * `sarif` - The log converted from the tool output
+  pub fn apply(&self, sarif: &mut crate::sarif::Sarif) {
+    if let Some(src_root) = self.src_root.as_ref() {
+      crate::uri::normalize_uris(sarif, src_root);
+    }
+    if self.fingerprints {
+      crate::fingerprint::add_partial_fingerprints(sarif);
+    }
+    if self.artifacts {
+      crate::artifacts::add_artifacts(sarif);
+    }
+  }
+}
+
+/// The command line arguments of [ConverterOptions]
+///
+/// Converter binaries flatten these into their own arguments with
+/// `#[command(flatten)]`, so that every converter takes the same options.
+#[cfg(feature = "clap")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "clap")))]
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct ConverterArgs {
+  /// populate partialFingerprints of every result with a hash of its rule, file
+  /// and source, which does not change when lines are added or removed above
+  #[arg(long)]
+  pub fingerprints: bool,
+  /// populate run.artifacts with the sha-256 hash, length, encoding and mime
+  /// type of every file referenced by a result
+  #[arg(long)]
+  pub artifacts: bool,
+  /// make artifact URIs relative to the root of the sources, defined as
+  /// %SRCROOT% in run.originalUriBaseIds; defaults to the git repository root
+  #[arg(long, value_name = "PATH")]
+  pub src_root: Option<Option<std::path::PathBuf>>,
+}
+
+#[cfg(feature = "clap")]
+impl ConverterArgs {
+  /// Returns the options given by the arguments
+  ///
+  /// A `--src-root` given without a path resolves to
+  /// [default_src_root](crate::uri::default_src_root).
+  pub fn options(&self) -> std::io::Result<ConverterOptions> {
+    let src_root = self
+      .src_root
+      .clone()
+      .map(|src_root| src_root.map_or_else(crate::uri::default_src_root, Ok))
+      .transpose()?;
+    Ok(
+      ConverterOptions::builder()
+        .fingerprints(self.fingerprints)
+        .artifacts(self.artifacts)
+        .src_root(src_root)
+        .build(),
+    )
+  }
+}
+
+/// A conversion from the native output of a tool into [crate::sarif::Sarif]
+///
+/// Every converter module exposes a type implementing this trait, which holds
+/// the options of the conversion. The trait is object safe, which makes it
+/// possible to select a converter at runtime (ex. as a `Box<dyn Converter>`),
+/// and to post-process the resulting [crate::sarif::Sarif] before serializing
+/// it. For that reason, options are not an associated type of the trait, which
+/// would require naming them in `dyn Converter<Options = ...>` and thus tie a
+/// trait object to converters sharing the same options. Options specific to a
+/// converter (ex. `ShellcheckOptions`) are given to its constructor instead,
+/// and the [ConverterOptions] shared by every converter are returned by
+/// [Converter::options].
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "hadolint-converters")]
+/// # {
+/// use serde_sarif::converters::{hadolint::HadolintConverter, Converter};
+///
+/// let output = r#"[{"code":"DL3008","column":1,"file":"Dockerfile","level":"warning","line":3,"message":"Pin versions in apt get install"}]"#;
+/// let converter: Box<dyn Converter> = Box::new(HadolintConverter::default());
+/// let sarif = converter.convert(&mut output.as_bytes()).unwrap();
+///
+/// assert_eq!(sarif.runs[0].tool.driver.name, converter.name());
+/// assert!(!converter.options().fingerprints);
+/// # }
+/// ```
+#[cfg(feature = "converters")]
+pub trait Converter {
+  /// Returns the name of the tool, written to `tool.driver.name`
+  fn name(&self) -> &'static str;
+
+  /// Returns a URI pointing to information about the tool, written to
+  /// `tool.driver.informationUri`
+  fn information_uri(&self) -> &'static str;
+
+  /// Returns the options shared by every converter which the conversion is
+  /// post-processed with
+  fn options(&self) -> &ConverterOptions;
+
+  /// Returns [crate::sarif::Sarif] converted from the tool output
+  ///
+  /// # Arguments
+  ///
+  /// * `reader` - A `BufRead` of the tool output
+  fn convert(
+    &self,
+    reader: &mut dyn std::io::BufRead,
+  ) -> anyhow::Result<crate::sarif::Sarif>;
+
+  /// Returns [crate::sarif::Sarif] serialized into a JSON stream
+  ///
+  /// # Arguments
+  ///
+  /// * `reader` - A `BufRead` of the tool output
+  /// * `writer` - A `Writer` to write the results to
+  fn convert_to_writer(
+    &self,
+    reader: &mut dyn std::io::BufRead,
+    writer: &mut dyn std::io::Write,
+  ) -> anyhow::Result<()> {
+    let sarif = self.convert(reader)?;
+    serde_json::to_writer_pretty(writer, &sarif)?;
+    Ok(())
+  }
+
+  /// Returns [crate::sarif::Sarif] serialized into a JSON string
+  ///
+  /// # Arguments
+  ///
+  /// * `reader` - A `BufRead` of the tool output
+  fn convert_to_string(
+    &self,
+    reader: &mut dyn std::io::BufRead,
+  ) -> anyhow::Result<String> {
+    let sarif = self.convert(reader)?;
+    let json = serde_json::to_string_pretty(&sarif)?;
+    Ok(json)
+  }
+}
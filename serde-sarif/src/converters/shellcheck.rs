@@ -1,4 +1,5 @@
 use std::{
+  cmp::Reverse,
   collections::HashMap,
   io::{BufRead, Write},
   str::FromStr,
@@ -8,6 +9,8 @@ use strum_macros::Display;
 use strum_macros::EnumString;
 use typed_builder::TypedBuilder;
 
+use super::columns::{self, ToolColumns};
+use super::{Converter, ConverterOptions};
 use crate::sarif::{self, ResultLevel};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -58,6 +61,30 @@ struct ShellcheckReplacement {
   end_column: i64,
 }
 
+/// The output format shellcheck was run with
+#[derive(Display, Debug, Default, EnumString, Copy, Clone, PartialEq, Eq)]
+pub enum ShellcheckFormat {
+  /// Output of `shellcheck -f json`
+  #[default]
+  #[strum(serialize = "json")]
+  Json,
+  /// Output of `shellcheck -f json1`
+  #[strum(serialize = "json1")]
+  Json1,
+}
+
+/// Options for [ShellcheckConverter]
+#[derive(Clone, Debug, Default, TypedBuilder)]
+#[builder(field_defaults(setter(into)))]
+pub struct ShellcheckOptions {
+  /// The format of the shellcheck output
+  #[builder(default)]
+  pub format: ShellcheckFormat,
+  /// The options shared by every converter
+  #[builder(default)]
+  pub common: ConverterOptions,
+}
+
 #[doc = "A value specifying the severity level of the result."]
 #[derive(
   Display, Debug, Serialize, Deserialize, EnumString, Copy, Clone, PartialEq,
@@ -117,6 +144,79 @@ impl From<&ShellcheckReplacement> for sarif::Region {
   }
 }
 
+impl ShellcheckReplacement {
+  // Orders replacements by their range. Insertions at the same position are
+  // ordered by insertion point, text inserted `beforeStart` of the position
+  // coming before text inserted `afterEnd` of it (ex. when quoting `$a$b`, the
+  // closing quote of `$a` comes before the opening quote of `$b`), then by
+  // precedence, highest first.
+  fn order(&self) -> (i64, i64, i64, i64, bool, Reverse<i64>) {
+    (
+      self.line,
+      self.column,
+      self.end_line,
+      self.end_column,
+      self.insertion_point == "afterEnd",
+      Reverse(self.precedence),
+    )
+  }
+
+  fn is_insertion(&self) -> bool {
+    (self.line, self.column) == (self.end_line, self.end_column)
+  }
+}
+
+impl From<&ShellcheckReplacement> for sarif::Replacement {
+  fn from(replacement: &ShellcheckReplacement) -> Self {
+    sarif::Replacement::builder()
+      .deleted_region(replacement)
+      .inserted_content(
+        sarif::ArtifactContent::builder()
+          .text(&replacement.replacement)
+          .build(),
+      )
+      .build()
+  }
+}
+
+impl From<(&ShellcheckResult, &ShellcheckFix)> for sarif::Fix {
+  fn from((result, fix): (&ShellcheckResult, &ShellcheckFix)) -> Self {
+    let mut replacements: Vec<&ShellcheckReplacement> =
+      fix.replacements.iter().collect();
+    replacements.sort_by_key(|replacement| replacement.order());
+
+    // SARIF replacements must not overlap, so insertions at the start of
+    // another replacement are merged into it, in order
+    let mut merged: Vec<ShellcheckReplacement> = vec![];
+    replacements
+      .into_iter()
+      .for_each(|replacement| match merged.last_mut() {
+        Some(last)
+          if last.is_insertion()
+            && (last.line, last.column)
+              == (replacement.line, replacement.column) =>
+        {
+          last.end_line = replacement.end_line;
+          last.end_column = replacement.end_column;
+          last.replacement.push_str(&replacement.replacement);
+        }
+        _ => merged.push(replacement.clone()),
+      });
+
+    sarif::Fix::builder()
+      .artifact_changes(vec![sarif::ArtifactChange::builder()
+        .artifact_location(result)
+        .replacements(
+          merged
+            .iter()
+            .map(sarif::Replacement::from)
+            .collect::<Vec<_>>(),
+        )
+        .build()])
+      .build()
+  }
+}
+
 impl From<&ShellcheckResult> for sarif::Region {
   fn from(result: &ShellcheckResult) -> Self {
     sarif::Region::builder()
@@ -128,18 +228,56 @@ impl From<&ShellcheckResult> for sarif::Region {
   }
 }
 
-fn process<R: BufRead>(mut reader: R, format: String) -> Result<sarif::Sarif> {
-  let mut data = String::new();
-  reader.read_to_string(&mut data)?;
+const NAME: &str = "shellcheck";
+const INFORMATION_URI: &str = "https://www.shellcheck.net/";
+
+/// Converts `shellcheck -f json` or `shellcheck -f json1` output into SARIF
+#[derive(Clone, Debug, Default)]
+pub struct ShellcheckConverter {
+  options: ShellcheckOptions,
+}
+
+impl ShellcheckConverter {
+  /// Returns a converter which converts as requested by `options`
+  pub fn new(options: ShellcheckOptions) -> Self {
+    Self { options }
+  }
+}
+
+impl Converter for ShellcheckConverter {
+  fn name(&self) -> &'static str {
+    NAME
+  }
+
+  fn information_uri(&self) -> &'static str {
+    INFORMATION_URI
+  }
+
+  fn options(&self) -> &ConverterOptions {
+    &self.options.common
+  }
+
+  fn convert(&self, reader: &mut dyn BufRead) -> Result<sarif::Sarif> {
+    let mut sarif = process(reader, self.options.format)?;
+    self.options.common.apply(&mut sarif);
+    Ok(sarif)
+  }
+}
+
+fn process<R: BufRead>(
+  reader: R,
+  format: ShellcheckFormat,
+) -> Result<sarif::Sarif> {
   let mut results = vec![];
   let mut map = HashMap::new();
   let mut rules = vec![];
 
-  let shellcheck_results: Vec<ShellcheckResult> = if format != "json1" {
-    serde_json::from_str(&data)?
-  } else {
-    let json1_format: JSON1Format = serde_json::from_str(&data)?;
-    json1_format.comments
+  let shellcheck_results: Vec<ShellcheckResult> = match format {
+    ShellcheckFormat::Json => serde_json::from_reader(reader)?,
+    ShellcheckFormat::Json1 => {
+      let json1_format: JSON1Format = serde_json::from_reader(reader)?;
+      json1_format.comments
+    }
   };
 
   shellcheck_results
@@ -167,43 +305,18 @@ fn process<R: BufRead>(mut reader: R, format: String) -> Result<sarif::Sarif> {
       if let Some(value) = map.get(&result.code.to_string()) {
         let level: sarif::ResultLevel =
           ShellcheckLevel::from_str(&result.level)?.into();
-        let fixes = if let Some(fix) = result.fix.as_ref() {
-          fix
-            .replacements
-            .iter()
-            .map(|fix| {
-              sarif::Fix::builder().description(&fix.replacement).build()
-            })
-            // .filter_map(|v| v.ok())
-            .collect()
-        } else {
-          vec![]
-        };
-        let related_locations = if let Some(fix) = result.fix.as_ref() {
-          fix
-            .replacements
-            .iter()
-            .map(|replacement| {
-              sarif::Location::builder()
-                .physical_location(
-                  sarif::PhysicalLocation::builder()
-                    .artifact_location(result)
-                    .region(replacement)
-                    .build(),
-                )
-                .build()
-            })
-            .collect()
-        } else {
-          vec![]
-        };
+        let fixes = result
+          .fix
+          .as_ref()
+          .filter(|fix| !fix.replacements.is_empty())
+          .map(|fix| vec![sarif::Fix::from((result, fix))])
+          .unwrap_or_default();
         results.push(
           sarif::Result::builder()
             .rule_id(result.code.to_string())
             .rule_index(*value)
             .message(&result.message)
             .locations(vec![result.into()])
-            .related_locations(related_locations)
             .fixes(fixes)
             .level(level)
             .build(),
@@ -212,7 +325,8 @@ fn process<R: BufRead>(mut reader: R, format: String) -> Result<sarif::Sarif> {
       Ok(())
     })?;
   let tool_component: sarif::ToolComponent = sarif::ToolComponent::builder()
-    .name("shellcheck")
+    .name(NAME)
+    .information_uri(INFORMATION_URI)
     .rules(rules)
     .build();
   let run = sarif::Run::builder()
@@ -220,12 +334,32 @@ fn process<R: BufRead>(mut reader: R, format: String) -> Result<sarif::Sarif> {
     .results(results)
     .build();
 
-  Ok(
-    sarif::Sarif::builder()
-      .version(sarif::Version::V2_1_0.to_string())
-      .runs(vec![run])
-      .build(),
-  )
+  let mut sarif = sarif::Sarif::builder()
+    .version(sarif::Version::V2_1_0.to_string())
+    .runs(vec![run])
+    .build();
+  match format {
+    // shellcheck counts characters, but expands tabs to the next multiple of 8
+    ShellcheckFormat::Json => {
+      columns::to_code_points(&mut sarif, ToolColumns::TabStops(8))
+    }
+    // json1 counts tabs as a single character
+    ShellcheckFormat::Json1 => sarif.runs.iter_mut().for_each(|run| {
+      run.column_kind = Some(
+        sarif::ResultColumnKind::UnicodeCodePoints
+          .to_string()
+          .into(),
+      )
+    }),
+  }
+  Ok(sarif)
+}
+
+// Anything other than "json1" has historically been treated as "json"
+fn options(format: &str) -> ShellcheckOptions {
+  ShellcheckOptions::builder()
+    .format(ShellcheckFormat::from_str(format).unwrap_or_default())
+    .build()
 }
 
 /// Returns [sarif::Sarif] serialized into a JSON stream
@@ -236,13 +370,12 @@ fn process<R: BufRead>(mut reader: R, format: String) -> Result<sarif::Sarif> {
 /// * `writer` - A `Writer` to write the results to
 /// * `format` - The format of the input
 pub fn parse_to_writer<R: BufRead, W: Write>(
-  reader: R,
-  writer: W,
+  mut reader: R,
+  mut writer: W,
   format: String,
 ) -> Result<()> {
-  let sarif = process(reader, format)?;
-  serde_json::to_writer_pretty(writer, &sarif)?;
-  Ok(())
+  ShellcheckConverter::new(options(&format))
+    .convert_to_writer(&mut reader, &mut writer)
 }
 
 /// Returns [sarif::Sarif] serialized into a JSON string
@@ -252,10 +385,8 @@ pub fn parse_to_writer<R: BufRead, W: Write>(
 /// * `reader` - A `BufRead` of shellcheck output
 /// * `format` - The format of the input
 pub fn parse_to_string<R: BufRead>(
-  reader: R,
+  mut reader: R,
   format: String,
 ) -> Result<String> {
-  let sarif = process(reader, format)?;
-  let json = serde_json::to_string_pretty(&sarif)?;
-  Ok(json)
+  ShellcheckConverter::new(options(&format)).convert_to_string(&mut reader)
 }
@@ -0,0 +1,313 @@
+//! Computes stable fingerprints which identify a result across analyses.
+//!
+//! The fingerprint of a result is a hash of its rule id, the path of its
+//! primary location and the normalized source of the region it points to.
+//! Unlike line numbers, it does not change when lines are inserted or removed
+//! above the result, which allows consumers (ex. GitHub code scanning) to
+//! track a result as code moves around.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_sarif::fingerprint::{
+//!   add_partial_fingerprints, PRIMARY_LOCATION_LINE_HASH,
+//! };
+//! use serde_sarif::sarif::Sarif;
+//!
+//! let mut sarif: Sarif = serde_json::from_str(
+//!   r#"{ "version": "2.1.0", "runs": [{ "tool": { "driver": { "name": "clippy" } }, "results": [{
+//!     "ruleId": "clippy::needless_return",
+//!     "message": { "text": "unneeded `return` statement" },
+//!     "locations": [{ "physicalLocation": {
+//!       "artifactLocation": { "uri": "src/lib.rs" },
+//!       "region": { "startLine": 3, "snippet": { "text": "  return 1;" } }
+//!     } }]
+//!   }] }] }"#
+//! ).unwrap();
+//!
+//! add_partial_fingerprints(&mut sarif);
+//! let result = &sarif.runs[0].results.as_ref().unwrap()[0];
+//! assert!(result
+//!   .partial_fingerprints
+//!   .as_ref()
+//!   .unwrap()
+//!   .contains_key(PRIMARY_LOCATION_LINE_HASH));
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+
+use crate::{sarif, uri, visit};
+
+/// The `partialFingerprints` key populated by [add_partial_fingerprints]
+///
+/// This is the key GitHub code scanning uses for its own fingerprints, which
+/// it only computes when the key is missing.
+pub const PRIMARY_LOCATION_LINE_HASH: &str = "primaryLocationLineHash";
+
+/// Returns the hex encoded fingerprint of a result
+///
+/// Whitespace in `context` is normalized, so that re-indenting the source of a
+/// result does not change its fingerprint.
+///
+/// # Arguments
+///
+/// * `rule_id` - The id of the rule of the result, if any
+/// * `path` - The path (or uri) of the primary location of the result
+/// * `context` - The source snippet of the primary location of the result
+pub fn fingerprint(rule_id: Option<&str>, path: &str, context: &str) -> String {
+  let mut hasher = Sha256::new();
+  hasher.update(rule_id.unwrap_or_default());
+  hasher.update([0]);
+  hasher.update(path);
+  hasher.update([0]);
+  context.split_whitespace().for_each(|word| {
+    hasher.update(word);
+    hasher.update(b" ");
+  });
+  hasher
+    .finalize()
+    .iter()
+    .map(|byte| format!("{:02x}", byte))
+    .collect()
+}
+
+// Returns the lines of the file at `path`, reading it at most once
+fn read_lines(
+  cache: &mut HashMap<PathBuf, Option<Vec<String>>>,
+  path: PathBuf,
+) -> Option<&Vec<String>> {
+  cache
+    .entry(path)
+    .or_insert_with_key(|path| {
+      fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.lines().map(String::from).collect())
+    })
+    .as_ref()
+}
+
+// Returns the source of `region` of the file at `path`, read from the file
+// when it exists and from the region snippet otherwise
+fn context(
+  cache: &mut HashMap<PathBuf, Option<Vec<String>>>,
+  path: Option<PathBuf>,
+  region: Option<&sarif::Region>,
+) -> Option<String> {
+  let region = region?;
+  let from_file =
+    region
+      .start_line
+      .filter(|line| *line > 0)
+      .and_then(|start_line| {
+        let end_line = region.end_line.unwrap_or(start_line).max(start_line);
+        let lines = read_lines(cache, path?)?;
+        let lines = lines.get((start_line - 1) as usize..end_line as usize)?;
+        Some(lines.join("\n"))
+      });
+  from_file.or_else(|| {
+    region
+      .snippet
+      .as_ref()
+      .and_then(|snippet| snippet.text.clone())
+  })
+}
+
+// Fingerprints the results of a run one at a time, caching the files it reads
+// and counting the occurrences of every fingerprint
+pub(crate) struct Fingerprinter {
+  original_uri_base_ids: Option<BTreeMap<String, sarif::ArtifactLocation>>,
+  cache: HashMap<PathBuf, Option<Vec<String>>>,
+  occurrences: HashMap<String, usize>,
+}
+
+impl Fingerprinter {
+  // Returns a fingerprinter which reads the files of a run whose base URIs
+  // are `original_uri_base_ids`
+  pub(crate) fn new(
+    original_uri_base_ids: Option<BTreeMap<String, sarif::ArtifactLocation>>,
+  ) -> Self {
+    Self {
+      original_uri_base_ids,
+      cache: HashMap::new(),
+      occurrences: HashMap::new(),
+    }
+  }
+
+  // Populates the fingerprint of `result` unless it already has one
+  pub(crate) fn add(
+    &mut self,
+    rule_id: Option<&str>,
+    result: &mut sarif::Result,
+  ) {
+    if result
+      .partial_fingerprints
+      .as_ref()
+      .is_some_and(|fingerprints| {
+        fingerprints.contains_key(PRIMARY_LOCATION_LINE_HASH)
+      })
+    {
+      return;
+    }
+    let physical_location = result
+      .locations
+      .as_ref()
+      .and_then(|locations| locations.first())
+      .and_then(|location| location.physical_location.as_ref());
+    let artifact_location = physical_location.and_then(|physical_location| {
+      physical_location.artifact_location.as_ref()
+    });
+    let uri = artifact_location
+      .and_then(|artifact_location| artifact_location.uri.as_deref())
+      .unwrap_or_default();
+    let path = artifact_location.and_then(|artifact_location| {
+      uri::resolve_path(artifact_location, self.original_uri_base_ids.as_ref())
+        .ok()
+    });
+    let context = context(
+      &mut self.cache,
+      path,
+      physical_location
+        .and_then(|physical_location| physical_location.region.as_ref()),
+    )
+    .or_else(|| result.message.text.clone())
+    .unwrap_or_default();
+
+    let hash = fingerprint(rule_id, uri, &context);
+    let occurrence = self.occurrences.entry(hash.clone()).or_default();
+    *occurrence += 1;
+    let fingerprint = format!("{}:{}", hash, occurrence);
+    result
+      .partial_fingerprints
+      .get_or_insert_with(Default::default)
+      .insert(PRIMARY_LOCATION_LINE_HASH.to_string(), fingerprint);
+  }
+}
+
+/// Populates the [PRIMARY_LOCATION_LINE_HASH] partial fingerprint of every
+/// result of `sarif` which does not already have one
+///
+/// The fingerprint is computed by [fingerprint] from the uri and the source of
+/// the region of the primary location of the result. The source is read from
+/// the file the uri resolves to (see [crate::uri::resolve_path]) and falls back
+/// to `region.snippet`, then to the message text. Logs whose URIs are
+/// normalized first (see [crate::uri::normalize_uris]) thus get the same
+/// fingerprints wherever the sources are checked out. Results of a run which
+/// share a fingerprint are disambiguated by appending the number of the
+/// occurrence (ex. `...:2`).
+///
+/// # Arguments
+///
+/// * `sarif` - The SARIF log to fingerprint
+pub fn add_partial_fingerprints(sarif: &mut sarif::Sarif) {
+  sarif.runs.iter_mut().for_each(|run| {
+    let mut fingerprinter =
+      Fingerprinter::new(run.original_uri_base_ids.clone());
+    let rule_ids: Vec<Option<String>> = run
+      .results
+      .iter()
+      .flatten()
+      .map(|result| visit::rule_id(run, result).map(String::from))
+      .collect();
+    run.results.iter_mut().flatten().zip(rule_ids).for_each(
+      |(result, rule_id)| fingerprinter.add(rule_id.as_deref(), result),
+    );
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn log(results: &[(&str, i64, &str)]) -> sarif::Sarif {
+    let results: Vec<String> = results
+      .iter()
+      .map(|(rule, line, snippet)| {
+        format!(
+          r#"{{"ruleId":"{}","message":{{"text":"m"}},"locations":[{{"physicalLocation":{{"artifactLocation":{{"uri":"does/not/exist.rs"}},"region":{{"startLine":{},"snippet":{{"text":"{}"}}}}}}}}]}}"#,
+          rule, line, snippet
+        )
+      })
+      .collect();
+    serde_json::from_str(&format!(
+      r#"{{"version":"2.1.0","runs":[{{"tool":{{"driver":{{"name":"clippy"}}}},"results":[{}]}}]}}"#,
+      results.join(",")
+    ))
+    .unwrap()
+  }
+
+  fn fingerprints(mut sarif: sarif::Sarif) -> Vec<String> {
+    add_partial_fingerprints(&mut sarif);
+    sarif.runs[0]
+      .results
+      .iter()
+      .flatten()
+      .map(|result| {
+        result.partial_fingerprints.as_ref().unwrap()
+          [PRIMARY_LOCATION_LINE_HASH]
+          .clone()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_fingerprints_tolerate_line_shifts() {
+    let before = fingerprints(log(&[("a", 3, "let x = 1;")]));
+    let after = fingerprints(log(&[("a", 10, "    let x =  1;")]));
+    assert_eq!(before, after);
+  }
+
+  #[test]
+  fn test_fingerprints_differ() {
+    let fingerprints = fingerprints(log(&[
+      ("a", 1, "let x = 1;"),
+      ("b", 1, "let x = 1;"),
+      ("a", 1, "let y = 1;"),
+      ("a", 5, "let x = 1;"),
+    ]));
+    assert_ne!(fingerprints[0], fingerprints[1]);
+    assert_ne!(fingerprints[0], fingerprints[2]);
+    assert!(fingerprints[0].ends_with(":1"));
+    assert!(fingerprints[3].ends_with(":2"));
+    assert_eq!(
+      fingerprints[0].split(':').next(),
+      fingerprints[3].split(':').next()
+    );
+  }
+
+  #[test]
+  fn test_fingerprints_read_through_base_ids() {
+    let src_root = uri::from_path(std::path::Path::new(concat!(
+      env!("CARGO_MANIFEST_DIR"),
+      "/src"
+    )));
+    let mut sarif: sarif::Sarif = serde_json::from_str(&format!(
+      r#"{{"version":"2.1.0","runs":[{{"tool":{{"driver":{{"name":"clippy"}}}},
+        "originalUriBaseIds":{{"%SRCROOT%":{{"uri":"{}/"}}}},
+        "results":[{{"ruleId":"a","message":{{"text":"m"}},"locations":[{{"physicalLocation":{{
+          "artifactLocation":{{"uri":"lib.rs","uriBaseId":"%SRCROOT%"}},
+          "region":{{"startLine":1}}}}}}]}}]}}]}}"#,
+      src_root
+    ))
+    .unwrap();
+    add_partial_fingerprints(&mut sarif);
+
+    let first_line =
+      fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/src/lib.rs"))
+        .unwrap()
+        .lines()
+        .next()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+      sarif.runs[0].results.as_ref().unwrap()[0]
+        .partial_fingerprints
+        .as_ref()
+        .unwrap()[PRIMARY_LOCATION_LINE_HASH],
+      format!("{}:1", fingerprint(Some("a"), "lib.rs", &first_line))
+    );
+  }
+}
@@ -0,0 +1,464 @@
+//! Applies the fixes of SARIF results to the files they refer to.
+//!
+//! A [sarif::Fix] describes a change to one or more artifacts as a list of
+//! [sarif::Replacement]s, each deleting a region of the original file and
+//! inserting new content in its place. [collect_fixes] resolves the fixes of
+//! every result of a log into byte ranges of the files on disk, checking that
+//! they do not overlap, and [FixPlan::apply] writes the edited files.
+//!
+//! Fixes converted from rustc suggestions record how likely they are to be
+//! correct in their [APPLICABILITY] property, and only those which are
+//! [Applicability::MachineApplicable] should be applied without review.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use serde_sarif::fix::{collect_fixes, Applicability};
+//! use serde_sarif::sarif::Sarif;
+//!
+//! let sarif: Sarif = serde_json::from_str(
+//!   r#"{ "version": "2.1.0", "runs": [{ "tool": { "driver": { "name": "clippy" } }, "results": [{
+//!     "message": { "text": "unneeded `return` statement" },
+//!     "fixes": [{ "artifactChanges": [{
+//!       "artifactLocation": { "uri": "src/lib.rs" },
+//!       "replacements": [{
+//!         "deletedRegion": { "startLine": 3, "startColumn": 3, "endColumn": 12 },
+//!         "insertedContent": { "text": "1" }
+//!       }]
+//!     }] }]
+//!   }] }] }"#
+//! ).unwrap();
+//!
+//! let plan = collect_fixes(&sarif, Applicability::MachineApplicable);
+//! plan.skipped.iter().for_each(|error| eprintln!("{}", error));
+//! plan.apply().unwrap();
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use strum_macros::{Display, EnumString};
+use thiserror::Error;
+
+use crate::sarif::ResultColumnKind;
+use crate::{region, sarif, uri};
+
+/// The key of the property bag of a fix holding the applicability of the
+/// suggestion it was converted from (ex. `MachineApplicable`)
+pub const APPLICABILITY: &str = "applicability";
+
+/// How likely a fix is to be correct, from the most to the least likely
+///
+/// The variants are those of the applicability of rustc suggestions.
+#[derive(
+  Clone,
+  Copy,
+  Debug,
+  PartialEq,
+  Eq,
+  PartialOrd,
+  Ord,
+  Deserialize,
+  Display,
+  EnumString,
+)]
+pub enum Applicability {
+  /// The fix is definitely what the user intended
+  MachineApplicable,
+  /// The fix may be what the user intended, but it is uncertain
+  MaybeIncorrect,
+  /// The fix contains placeholders (ex. `(...)`) which must be filled in
+  HasPlaceholders,
+  /// The applicability of the fix is unknown
+  Unspecified,
+}
+
+impl Applicability {
+  /// Returns the applicability recorded in the [APPLICABILITY] property of
+  /// `fix`
+  ///
+  /// Fixes without the property (ex. those of shellcheck) are
+  /// [Applicability::MachineApplicable], and those with an unknown value are
+  /// [Applicability::Unspecified].
+  ///
+  /// # Arguments
+  ///
+  /// * `fix` - The fix whose applicability to return
+  pub fn of(fix: &sarif::Fix) -> Self {
+    match fix.properties.as_ref().and_then(|properties| {
+      properties.additional_properties.get(APPLICABILITY)
+    }) {
+      Some(value) => Self::deserialize(value).unwrap_or(Self::Unspecified),
+      None => Self::MachineApplicable,
+    }
+  }
+}
+
+/// An edit of the contents of a file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+  /// The byte range of the original contents which is replaced
+  pub range: Range<usize>,
+  /// The text inserted in place of `range`
+  pub text: String,
+}
+
+impl Edit {
+  // Returns whether applying both edits is ambiguous: their ranges intersect,
+  // or they insert at the same position
+  fn overlaps(&self, other: &Edit) -> bool {
+    (self.range.start < other.range.end && other.range.start < self.range.end)
+      || (self.range.start == other.range.start
+        && (self.range.is_empty() || other.range.is_empty()))
+  }
+}
+
+/// The edits to apply to a single file
+#[derive(Clone, Debug)]
+pub struct FileEdits {
+  /// The path of the file
+  pub path: PathBuf,
+  /// The contents of the file before the edits are applied
+  pub original: String,
+  /// The edits, sorted by position and not overlapping
+  pub edits: Vec<Edit>,
+}
+
+impl FileEdits {
+  /// Returns the contents of the file after the edits are applied
+  pub fn edited(&self) -> String {
+    let mut edited = String::with_capacity(self.original.len());
+    let mut position = 0;
+    self.edits.iter().for_each(|edit| {
+      edited.push_str(&self.original[position..edit.range.start]);
+      edited.push_str(&edit.text);
+      position = edit.range.end;
+    });
+    edited.push_str(&self.original[position..]);
+    edited
+  }
+}
+
+/// A reason why the fix of a result could not be applied
+#[derive(Error, Debug)]
+pub enum FixError {
+  #[error("{path}: {source}")]
+  Io {
+    path: PathBuf,
+    #[source]
+    source: io::Error,
+  },
+  #[error(transparent)]
+  Uri(#[from] uri::ResolveError),
+  #[error("{0}: fix refers to a region outside of the file")]
+  InvalidRegion(PathBuf),
+  #[error("{0}: replacements of a fix overlap")]
+  OverlappingReplacements(PathBuf),
+  #[error("{0}: fix overlaps the fix of another result")]
+  Conflict(PathBuf),
+  #[error("fix is {0}, which is less likely to be correct than required")]
+  Applicability(Applicability),
+}
+
+/// The fixes of a SARIF log, resolved into edits of the files on disk
+#[derive(Debug, Default)]
+pub struct FixPlan {
+  /// The files to edit, sorted by path
+  pub files: Vec<FileEdits>,
+  /// The number of results whose fix is applied
+  pub fixed: usize,
+  /// The reasons why the fixes of the other results are not applied
+  pub skipped: Vec<FixError>,
+}
+
+impl FixPlan {
+  /// Writes the edited contents of every file
+  pub fn apply(&self) -> io::Result<()> {
+    self
+      .files
+      .iter()
+      .try_for_each(|file| fs::write(&file.path, file.edited()))
+  }
+}
+
+// Returns the edits of `fix`, grouped by file and sorted by position
+fn fix_edits<F: FnMut(&Path) -> io::Result<String>>(
+  contents: &mut HashMap<PathBuf, String>,
+  read: &mut F,
+  fix: &sarif::Fix,
+  run: &sarif::Run,
+  column_kind: ResultColumnKind,
+) -> Result<BTreeMap<PathBuf, Vec<Edit>>, FixError> {
+  let mut edits: BTreeMap<PathBuf, Vec<Edit>> = BTreeMap::new();
+  for artifact_change in &fix.artifact_changes {
+    let path = uri::resolve_path(
+      &artifact_change.artifact_location,
+      run.original_uri_base_ids.as_ref(),
+    )?;
+    if !contents.contains_key(&path) {
+      let source = read(&path).map_err(|source| FixError::Io {
+        path: path.clone(),
+        source,
+      })?;
+      contents.insert(path.clone(), source);
+    }
+    let source = &contents[&path];
+    for replacement in &artifact_change.replacements {
+      let range =
+        region::byte_range(source, &replacement.deleted_region, column_kind)
+          .ok_or_else(|| FixError::InvalidRegion(path.clone()))?;
+      let text = replacement
+        .inserted_content
+        .as_ref()
+        .and_then(|content| content.text.clone())
+        .unwrap_or_default();
+      edits
+        .entry(path.clone())
+        .or_default()
+        .push(Edit { range, text });
+    }
+  }
+  for (path, edits) in edits.iter_mut() {
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+    if edits.windows(2).any(|pair| pair[0].overlaps(&pair[1])) {
+      return Err(FixError::OverlappingReplacements(path.clone()));
+    }
+  }
+  Ok(edits)
+}
+
+fn collect_fixes_with<F: FnMut(&Path) -> io::Result<String>>(
+  sarif: &sarif::Sarif,
+  applicability: Applicability,
+  mut read: F,
+) -> FixPlan {
+  let mut contents = HashMap::new();
+  let mut accepted: BTreeMap<PathBuf, Vec<Edit>> = BTreeMap::new();
+  let mut plan = FixPlan::default();
+
+  sarif.runs.iter().for_each(|run| {
+    let column_kind = region::column_kind(run);
+    run
+      .results
+      .iter()
+      .flatten()
+      .filter_map(|result| {
+        result.fixes.as_ref().filter(|fixes| !fixes.is_empty())
+      })
+      .for_each(|fixes| {
+        // the first fix which is likely enough to be correct is applied, as
+        // the others are alternatives
+        let edits = fixes
+          .iter()
+          .find(|fix| Applicability::of(fix) <= applicability)
+          .ok_or_else(|| {
+            FixError::Applicability(
+              fixes
+                .iter()
+                .map(Applicability::of)
+                .min()
+                .unwrap_or(Applicability::Unspecified),
+            )
+          })
+          .and_then(|fix| {
+            fix_edits(&mut contents, &mut read, fix, run, column_kind)
+          })
+          .and_then(|edits| {
+            // a fix whose edits have all been accepted already (ex. the same
+            // fix reported by two results) is not treated as a conflict
+            let applied = edits.iter().all(|(path, edits)| {
+              accepted.get(path).is_some_and(|file| {
+                edits.iter().all(|edit| file.contains(edit))
+              })
+            });
+            if applied {
+              return Ok(BTreeMap::new());
+            }
+            match edits.iter().find(|(path, edits)| {
+              accepted.get(*path).is_some_and(|file| {
+                edits
+                  .iter()
+                  .any(|edit| file.iter().any(|other| edit.overlaps(other)))
+              })
+            }) {
+              Some((path, _)) => Err(FixError::Conflict(path.clone())),
+              None => Ok(edits),
+            }
+          });
+        match edits {
+          Ok(edits) => {
+            plan.fixed += 1;
+            edits.into_iter().for_each(|(path, edits)| {
+              accepted.entry(path).or_default().extend(edits);
+            });
+          }
+          Err(error) => plan.skipped.push(error),
+        }
+      });
+  });
+
+  plan.files = accepted
+    .into_iter()
+    .filter(|(_, edits)| !edits.is_empty())
+    .map(|(path, mut edits)| {
+      edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+      FileEdits {
+        original: contents.remove(&path).unwrap_or_default(),
+        path,
+        edits,
+      }
+    })
+    .collect();
+  plan
+}
+
+/// Returns the edits of the fixes of every result of `sarif`
+///
+/// Only the first fix of a result which is at least as likely to be correct as
+/// `applicability` is considered, as the others are alternatives. The uris of the artifacts a fix changes are resolved with
+/// [resolve_path](crate::uri::resolve_path), relative to the current directory
+/// when they have no base. A fix is applied entirely or not at all: fixes
+/// which refer to a file which cannot be read or to a region outside of it,
+/// whose replacements overlap, or which overlap the fix of a previous result
+/// are skipped, and the reason is recorded in [FixPlan::skipped], as are
+/// results whose fixes are all less likely to be correct than `applicability`.
+///
+/// # Arguments
+///
+/// * `sarif` - The SARIF log whose fixes to collect
+/// * `applicability` - The least likely to be correct applicability of the
+///   fixes to apply, which should be [Applicability::MachineApplicable] unless
+///   the changes are reviewed
+pub fn collect_fixes(
+  sarif: &sarif::Sarif,
+  applicability: Applicability,
+) -> FixPlan {
+  collect_fixes_with(sarif, applicability, |path| fs::read_to_string(path))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SOURCE: &str = "fn main() {\n  return 1;\n}\n";
+
+  fn log(fixes: &[&str]) -> sarif::Sarif {
+    let results: Vec<String> = fixes
+      .iter()
+      .map(|replacements| {
+        format!(
+          r#"{{"message":{{"text":"m"}},"fixes":[{{"artifactChanges":[{{"artifactLocation":{{"uri":"lib.rs"}},"replacements":[{}]}}]}}]}}"#,
+          replacements
+        )
+      })
+      .collect();
+    serde_json::from_str(&format!(
+      r#"{{"version":"2.1.0","runs":[{{"tool":{{"driver":{{"name":"clippy"}}}},"results":[{}]}}]}}"#,
+      results.join(",")
+    ))
+    .unwrap()
+  }
+
+  fn plan(fixes: &[&str]) -> FixPlan {
+    collect_fixes_with(&log(fixes), Applicability::MachineApplicable, |_| {
+      Ok(SOURCE.to_string())
+    })
+  }
+
+  #[test]
+  fn test_collect_fixes() {
+    let plan = plan(&[
+      r#"{"deletedRegion":{"startLine":2,"startColumn":3,"endColumn":11},"insertedContent":{"text":"1"}},
+         {"deletedRegion":{"startLine":2,"startColumn":12,"endColumn":12},"insertedContent":{"text":" // one"}}"#,
+      r#"{"deletedRegion":{"byteOffset":3,"byteLength":4},"insertedContent":{"text":"start"}}"#,
+      r#"{"deletedRegion":{"byteOffset":3,"byteLength":4},"insertedContent":{"text":"start"}}"#,
+    ]);
+    assert_eq!(plan.fixed, 3);
+    assert!(plan.skipped.is_empty());
+    assert_eq!(plan.files.len(), 1);
+    assert_eq!(plan.files[0].edits.len(), 3);
+    assert_eq!(plan.files[0].edited(), "fn start() {\n  1; // one\n}\n");
+  }
+
+  #[test]
+  fn test_collect_fixes_applicability() {
+    let fix = |applicability: &str, text: &str| {
+      format!(
+        r#"{{"properties":{{"applicability":"{}"}},"artifactChanges":[{{"artifactLocation":{{"uri":"lib.rs"}},"replacements":[{{"deletedRegion":{{"byteOffset":3,"byteLength":4}},"insertedContent":{{"text":"{}"}}}}]}}]}}"#,
+        applicability, text
+      )
+    };
+    let sarif: sarif::Sarif = serde_json::from_str(&format!(
+      r#"{{"version":"2.1.0","runs":[{{"tool":{{"driver":{{"name":"clippy"}}}},"results":[{{"message":{{"text":"m"}},"fixes":[{},{}]}}]}}]}}"#,
+      fix("MaybeIncorrect", "maybe"),
+      fix("MachineApplicable", "machine")
+    ))
+    .unwrap();
+    let edited = |applicability| {
+      let plan =
+        collect_fixes_with(&sarif, applicability, |_| Ok(SOURCE.to_string()));
+      plan.files.first().map(FileEdits::edited)
+    };
+    assert_eq!(
+      edited(Applicability::MachineApplicable).unwrap(),
+      "fn machine() {\n  return 1;\n}\n"
+    );
+    assert_eq!(
+      edited(Applicability::MaybeIncorrect).unwrap(),
+      "fn maybe() {\n  return 1;\n}\n"
+    );
+
+    let sarif: sarif::Sarif = serde_json::from_str(&format!(
+      r#"{{"version":"2.1.0","runs":[{{"tool":{{"driver":{{"name":"clippy"}}}},"results":[{{"message":{{"text":"m"}},"fixes":[{}]}}]}}]}}"#,
+      fix("HasPlaceholders", "(...)")
+    ))
+    .unwrap();
+    let plan =
+      collect_fixes_with(&sarif, Applicability::MaybeIncorrect, |_| {
+        Ok(SOURCE.to_string())
+      });
+    assert_eq!(plan.fixed, 0);
+    assert!(matches!(
+      plan.skipped.as_slice(),
+      [FixError::Applicability(Applicability::HasPlaceholders)]
+    ));
+  }
+
+  #[test]
+  fn test_collect_fixes_skips_overlaps() {
+    let plan = plan(&[
+      r#"{"deletedRegion":{"startLine":2,"startColumn":3,"endColumn":11},"insertedContent":{"text":"1"}}"#,
+      r#"{"deletedRegion":{"startLine":2,"startColumn":10,"endColumn":11}},
+         {"deletedRegion":{"startLine":2,"startColumn":9,"endColumn":11}}"#,
+      r#"{"deletedRegion":{"startLine":2}}"#,
+      r#"{"deletedRegion":{"startLine":9}}"#,
+    ]);
+    assert_eq!(plan.fixed, 1);
+    assert!(matches!(
+      plan.skipped.as_slice(),
+      [
+        FixError::OverlappingReplacements(_),
+        FixError::Conflict(_),
+        FixError::InvalidRegion(_)
+      ]
+    ));
+    assert_eq!(plan.files[0].edited(), "fn main() {\n  1;\n}\n");
+  }
+
+  #[test]
+  fn test_collect_fixes_shared_insertion_conflicts() {
+    let plan = plan(&[
+      r#"{"deletedRegion":{"startLine":2,"startColumn":3,"endColumn":3},"insertedContent":{"text":"("}},
+         {"deletedRegion":{"startLine":2,"startColumn":11,"endColumn":11},"insertedContent":{"text":")"}}"#,
+      r#"{"deletedRegion":{"startLine":2,"startColumn":3,"endColumn":3},"insertedContent":{"text":"("}},
+         {"deletedRegion":{"startLine":2,"startColumn":10,"endColumn":10},"insertedContent":{"text":")"}}"#,
+    ]);
+    assert_eq!(plan.fixed, 1);
+    assert!(matches!(plan.skipped.as_slice(), [FixError::Conflict(_)]));
+    assert_eq!(plan.files[0].edited(), "fn main() {\n  (return 1);\n}\n");
+  }
+}
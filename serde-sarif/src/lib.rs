@@ -1,4 +1,5 @@
 #![doc(html_root_url = "https://docs.rs/serde-sarif/0.8.0")]
+#![cfg_attr(doc_cfg, feature(doc_cfg))]
 
 //! This crate provides a type safe [serde](https://serde.rs/) compatible
 //! [SARIF](https://sarifweb.azurewebsites.net/) structure. It is intended
@@ -42,7 +43,7 @@
 //! ```rust
 //! use serde_sarif::sarif::Message;
 //!
-//! let message = Message::default()
+//! let message = Message::builder()
 //!   .id("id")
 //!   .build();
 //! ```
@@ -50,6 +51,39 @@
 //! This uses [`TypedBuilder`](typed_builder::TypedBuilder)
 //! for compile time type checking.
 //!
+//! ## Utilities
+//!
+//! Besides the SARIF structures, this crate provides utilities which operate
+//! on [sarif::Sarif] logs:
+//!
+//! - [merge](merge::merge) combines multiple logs into a single log
+//! - [compare](baseline::compare) compares a log against a baseline log,
+//!   setting the `baselineState` of every result
+//! - [add_artifacts](artifacts::add_artifacts) describes the files referenced
+//!   by results in `run.artifacts`, with their hash, length and encoding
+//!   (requires the **artifacts** feature)
+//! - [add_partial_fingerprints](fingerprint::add_partial_fingerprints)
+//!   populates line shift tolerant `partialFingerprints` of every result
+//!   (requires the **fingerprint** feature)
+//! - [collect_fixes](fix::collect_fixes) resolves the fixes of every result
+//!   into non-overlapping edits, which can be applied to the files on disk
+//! - [normalize_uris](uri::normalize_uris) makes artifact URIs relative to the
+//!   root of the sources, defined as `%SRCROOT%` in `run.originalUriBaseIds`
+//! - [byte_range](region::byte_range) resolves a region given by line and
+//!   column (in either `columnKind`), character offset or byte offset into a
+//!   byte range of its text
+//! - [resolve](uri::resolve) resolves the URI of an artifact against its
+//!   (possibly chained) `uriBaseId`
+//! - [rule_id](visit::rule_id) returns the id of the rule of a result, which
+//!   may be given by its index in the rules of the run
+//! - [validate](validate::validate) checks a log against the SARIF schema and
+//!   specification (requires the **validate** feature)
+//! - [for_each_result](stream::for_each_result) reads the results of a log
+//!   one at a time, without holding every result in memory
+//! - [SarifWriter](stream::SarifWriter) writes a log as its results are
+//!   produced, which the `clippy` and `miri` converters use to convert output
+//!   of any size in constant memory
+//!
 //! ## Internal Implementation Details
 //!
 //! The root [sarif::Sarif] struct is automatically generated from the latest Sarif
@@ -68,16 +102,47 @@
 //! ```
 //!
 //! ### Converters
+//! - **converters** Provides the [`Converter`](converters::Converter) trait
+//!   and the post-processing shared by converters, which enables the
+//!   **artifacts** and **fingerprint** features (enabled by every converter)
 //! - **clang-tidy-converters** Provides conversions between clang tidy and SARIF types
 //! - **clippy-converters** Provides conversions between Clippy and SARIF types
 //! - **hadolint-converters** Provides conversions between hadolint and SARIF types
 //! - **shellcheck-converters** Provides conversions between shellcheck and SARIF types
 //!
+//! Each converter implements the common
+//! [`Converter`](converters::Converter) trait, which allows selecting a
+//! converter at runtime and post-processing the converted [sarif::Sarif].
+//!
 //! ### Other
 //!
+//! - **artifacts** Provides [artifacts], which hashes the files referenced by
+//!   results with SHA-256
+//! - **fingerprint** Provides [fingerprint], which hashes the source of
+//!   results with SHA-256
+//! - **clap** Provides [`ConverterArgs`](converters::ConverterArgs), the
+//!   command line arguments of the options shared by converters
 //! - **opt-builder** Enables
 //!   [`TypedBuilder`](typed_builder::TypedBuilder)
 //!   fallback setters for easier conditional building
+//! - **validate** Provides validation of logs against the SARIF schema and
+//!   specification
 
+#[cfg(feature = "artifacts")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "artifacts")))]
+pub mod artifacts;
+pub mod baseline;
 pub mod converters;
+#[cfg(feature = "fingerprint")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "fingerprint")))]
+pub mod fingerprint;
+pub mod fix;
+pub mod merge;
+pub mod region;
 pub mod sarif;
+pub mod stream;
+pub mod uri;
+#[cfg(feature = "validate")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "validate")))]
+pub mod validate;
+pub mod visit;
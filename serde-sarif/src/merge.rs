@@ -0,0 +1,324 @@
+//! Combines multiple SARIF logs into a single log.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_sarif::merge::{merge, MergeOptions};
+//! use serde_sarif::sarif::Sarif;
+//!
+//! let clippy: Sarif = serde_json::from_str(
+//!   r#"{ "version": "2.1.0", "runs": [{ "tool": { "driver": { "name": "clippy" } } }] }"#
+//! ).unwrap();
+//! let shellcheck: Sarif = serde_json::from_str(
+//!   r#"{ "version": "2.1.0", "runs": [{ "tool": { "driver": { "name": "shellcheck" } } }] }"#
+//! ).unwrap();
+//!
+//! let merged = merge(vec![clippy, shellcheck], &MergeOptions::default());
+//! assert_eq!(merged.runs.len(), 2);
+//! ```
+
+use typed_builder::TypedBuilder;
+
+use crate::{sarif, visit};
+
+/// Options for [merge]
+#[derive(Clone, Debug, Default, TypedBuilder)]
+#[builder(field_defaults(setter(into)))]
+pub struct MergeOptions {
+  /// Combine runs produced by the same tool (same `tool.driver.name`) into a
+  /// single run
+  #[builder(default)]
+  pub coalesce_runs: bool,
+}
+
+/// Returns a single [sarif::Sarif] containing the runs of every log in `logs`
+///
+/// When [MergeOptions::coalesce_runs] is set, runs whose `tool.driver.name`
+/// match are combined into a single run. The rules, artifacts and invocations
+/// of the combined runs are de-duplicated and the `ruleIndex`, `rule.index`,
+/// `artifactLocation.index` and `provenance.invocationIndex` of every result are
+/// re-indexed, so that the merged log stays internally consistent.
+///
+/// Runs which use other run level arrays referenced by index (ex.
+/// `logicalLocations` or `threadFlowLocations`), or which disagree on
+/// `columnKind`, are never combined and are kept as separate runs instead.
+///
+/// # Arguments
+///
+/// * `logs` - The SARIF logs to merge, in order
+/// * `options` - Options which control how runs are merged
+pub fn merge<I: IntoIterator<Item = sarif::Sarif>>(
+  logs: I,
+  options: &MergeOptions,
+) -> sarif::Sarif {
+  let mut runs: Vec<sarif::Run> = vec![];
+
+  logs.into_iter().flat_map(|log| log.runs).for_each(|run| {
+    let target = if options.coalesce_runs {
+      runs.iter_mut().find(|target| can_coalesce(target, &run))
+    } else {
+      None
+    };
+    match target {
+      Some(target) => coalesce(target, run),
+      None => runs.push(run),
+    }
+  });
+
+  sarif::Sarif::builder()
+    .version(sarif::Version::V2_1_0.to_string())
+    .schema(sarif::SCHEMA_URL)
+    .runs(runs)
+    .build()
+}
+
+// Returns true if the run uses run level arrays which results may reference by
+// index, and which are not re-indexed when coalescing.
+fn has_unsupported_references(run: &sarif::Run) -> bool {
+  run.addresses.is_some()
+    || run.graphs.is_some()
+    || run.logical_locations.is_some()
+    || run.thread_flow_locations.is_some()
+    || run.web_requests.is_some()
+    || run.web_responses.is_some()
+    || run.tool.extensions.is_some()
+}
+
+fn can_coalesce(target: &sarif::Run, source: &sarif::Run) -> bool {
+  let uri_base_ids_agree = match (
+    target.original_uri_base_ids.as_ref(),
+    source.original_uri_base_ids.as_ref(),
+  ) {
+    (Some(target_ids), Some(source_ids)) => source_ids
+      .iter()
+      .all(|(key, value)| target_ids.get(key).is_none_or(|v| v == value)),
+    _ => true,
+  };
+
+  target.tool.driver.name == source.tool.driver.name
+    && target.column_kind == source.column_kind
+    && uri_base_ids_agree
+    && !has_unsupported_references(target)
+    && !has_unsupported_references(source)
+}
+
+// Appends `rules` to the driver rules (skipping rules whose id already exists),
+// returning the new index of each appended rule.
+fn merge_rules(
+  driver: &mut sarif::ToolComponent,
+  rules: Option<Vec<sarif::ReportingDescriptor>>,
+) -> Vec<i64> {
+  let rules = match rules {
+    Some(rules) => rules,
+    None => return vec![],
+  };
+  let target_rules = driver.rules.get_or_insert_with(Vec::new);
+  rules
+    .into_iter()
+    .map(|rule| {
+      let index = target_rules
+        .iter()
+        .position(|target_rule| target_rule.id == rule.id)
+        .unwrap_or_else(|| {
+          target_rules.push(rule);
+          target_rules.len() - 1
+        });
+      index as i64
+    })
+    .collect()
+}
+
+// Appends `artifacts` to the run artifacts (skipping artifacts whose location
+// already exists), returning the new index of each appended artifact.
+fn merge_artifacts(
+  run: &mut sarif::Run,
+  artifacts: Option<Vec<sarif::Artifact>>,
+) -> Vec<i64> {
+  let artifacts = match artifacts {
+    Some(artifacts) => artifacts,
+    None => return vec![],
+  };
+  let target_artifacts = run.artifacts.get_or_insert_with(Vec::new);
+  let mut appended = vec![];
+  let indices: Vec<i64> = artifacts
+    .into_iter()
+    .map(|artifact| {
+      let existing = artifact.location.as_ref().and_then(|location| {
+        target_artifacts.iter().position(|target_artifact| {
+          target_artifact
+            .location
+            .as_ref()
+            .is_some_and(|target_location| {
+              target_location.uri == location.uri
+                && target_location.uri_base_id == location.uri_base_id
+            })
+        })
+      });
+      let index = existing.unwrap_or_else(|| {
+        target_artifacts.push(artifact);
+        appended.push(target_artifacts.len() - 1);
+        target_artifacts.len() - 1
+      });
+      index as i64
+    })
+    .collect();
+
+  // parent indices of the appended artifacts still refer to the source run
+  appended.into_iter().for_each(|index| {
+    let artifact = &mut target_artifacts[index];
+    artifact.parent_index = artifact
+      .parent_index
+      .and_then(|parent_index| indices.get(parent_index as usize).copied());
+  });
+
+  indices
+}
+
+fn reindex(indices: &[i64], index: &mut Option<i64>) {
+  if let Some(new_index) =
+    index.and_then(|index| indices.get(index as usize).copied())
+  {
+    *index = Some(new_index);
+  }
+}
+
+fn coalesce(target: &mut sarif::Run, mut source: sarif::Run) {
+  let rule_indices =
+    merge_rules(&mut target.tool.driver, source.tool.driver.rules.take());
+  let artifact_indices = merge_artifacts(target, source.artifacts.take());
+  let invocation_offset = target
+    .invocations
+    .as_ref()
+    .map_or(0, |invocations| invocations.len());
+  if let Some(invocations) = source.invocations.take() {
+    target
+      .invocations
+      .get_or_insert_with(Vec::new)
+      .extend(invocations);
+  }
+
+  if let Some(original_uri_base_ids) = source.original_uri_base_ids.take() {
+    let target_ids = target
+      .original_uri_base_ids
+      .get_or_insert_with(Default::default);
+    original_uri_base_ids.into_iter().for_each(|(key, value)| {
+      target_ids.entry(key).or_insert(value);
+    });
+  }
+
+  let mut results = source.results.take().unwrap_or_default();
+  results.iter_mut().for_each(|result| {
+    reindex(&rule_indices, &mut result.rule_index);
+    if let Some(rule) = result.rule.as_mut() {
+      // rules of tool extensions are not part of the driver rules
+      if rule.tool_component.is_none() {
+        reindex(&rule_indices, &mut rule.index);
+      }
+    }
+    visit::for_each_artifact_location_mut(result, &mut |artifact_location| {
+      reindex(&artifact_indices, &mut artifact_location.index);
+    });
+    if let Some(invocation_index) = result
+      .provenance
+      .as_mut()
+      .and_then(|provenance| provenance.invocation_index.as_mut())
+    {
+      *invocation_index += invocation_offset as i64;
+    }
+  });
+  if !results.is_empty() {
+    target.results.get_or_insert_with(Vec::new).extend(results);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn log(json: &str) -> sarif::Sarif {
+    serde_json::from_str(json).unwrap()
+  }
+
+  #[test]
+  fn test_merge_concatenates_runs() {
+    let merged = merge(
+      vec![
+        log(r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"a"}}}]}"#),
+        log(r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"a"}}}]}"#),
+      ],
+      &MergeOptions::default(),
+    );
+    assert_eq!(merged.runs.len(), 2);
+  }
+
+  #[test]
+  fn test_merge_coalesces_runs_and_reindexes() {
+    let first = log(
+      r#"{"version":"2.1.0","runs":[{
+        "tool":{"driver":{"name":"clippy","rules":[{"id":"a"},{"id":"b"}]}},
+        "artifacts":[{"location":{"uri":"src/lib.rs"}}],
+        "results":[{"message":{"text":"1"},"ruleId":"b","ruleIndex":1,
+          "locations":[{"physicalLocation":{"artifactLocation":{"uri":"src/lib.rs","index":0}}}]}]
+      }]}"#,
+    );
+    let second = log(
+      r#"{"version":"2.1.0","runs":[{
+        "tool":{"driver":{"name":"clippy","rules":[{"id":"c"},{"id":"a"}]}},
+        "artifacts":[{"location":{"uri":"src/main.rs"}},{"location":{"uri":"src/lib.rs"}}],
+        "results":[
+          {"message":{"text":"2"},"ruleId":"c","ruleIndex":0,
+            "locations":[{"physicalLocation":{"artifactLocation":{"uri":"src/main.rs","index":0}}}]},
+          {"message":{"text":"3"},"ruleId":"a","rule":{"id":"a","index":1},
+            "locations":[{"physicalLocation":{"artifactLocation":{"uri":"src/lib.rs","index":1}}}]}
+        ]
+      }]}"#,
+    );
+    let merged = merge(
+      vec![first, second],
+      &MergeOptions::builder().coalesce_runs(true).build(),
+    );
+
+    assert_eq!(merged.runs.len(), 1);
+    let run = &merged.runs[0];
+    let rule_ids: Vec<_> = run
+      .tool
+      .driver
+      .rules
+      .iter()
+      .flatten()
+      .map(|rule| rule.id.as_str())
+      .collect();
+    assert_eq!(rule_ids, vec!["a", "b", "c"]);
+    assert_eq!(run.artifacts.as_ref().unwrap().len(), 2);
+
+    let results = run.results.as_ref().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1].rule_index, Some(2));
+    assert_eq!(results[2].rule.as_ref().unwrap().index, Some(0));
+
+    let artifact_index = |result: &sarif::Result| {
+      result.locations.as_ref().unwrap()[0]
+        .physical_location
+        .as_ref()
+        .unwrap()
+        .artifact_location
+        .as_ref()
+        .unwrap()
+        .index
+    };
+    assert_eq!(artifact_index(&results[1]), Some(1));
+    assert_eq!(artifact_index(&results[2]), Some(0));
+  }
+
+  #[test]
+  fn test_merge_does_not_coalesce_different_tools() {
+    let merged = merge(
+      vec![
+        log(r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"a"}}}]}"#),
+        log(r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"b"}}}]}"#),
+      ],
+      &MergeOptions::builder().coalesce_runs(true).build(),
+    );
+    assert_eq!(merged.runs.len(), 2);
+  }
+}
@@ -0,0 +1,197 @@
+//! Resolves [sarif::Region]s into byte ranges of the text they refer to.
+//!
+//! A region of a text file may be given by line and column, by character
+//! offset, or by byte offset. Columns are counted in the unit given by
+//! `run.columnKind`: UTF-16 code units by default (as most editors written in
+//! JavaScript, Java or C# do), or Unicode code points. [byte_range] converts
+//! any of these forms into the byte range of a Rust string, so that regions of
+//! non-ASCII text are sliced at the right characters.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_sarif::region::byte_range;
+//! use serde_sarif::sarif::{Region, ResultColumnKind};
+//!
+//! let contents = "let café = \"😀\";\n";
+//! let region: Region = serde_json::from_str(
+//!   r#"{ "startLine": 1, "startColumn": 13, "endColumn": 15 }"#
+//! ).unwrap();
+//!
+//! let range = byte_range(contents, &region, ResultColumnKind::Utf16CodeUnits);
+//! assert_eq!(&contents[range.unwrap()], "😀");
+//! let range = byte_range(contents, &region, ResultColumnKind::UnicodeCodePoints);
+//! assert_eq!(&contents[range.unwrap()], "😀\"");
+//! ```
+
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::sarif;
+use crate::sarif::ResultColumnKind;
+
+/// Returns the unit in which the columns of `run` are counted, which defaults
+/// to UTF-16 code units
+///
+/// # Arguments
+///
+/// * `run` - The run whose `columnKind` to read
+pub fn column_kind(run: &sarif::Run) -> ResultColumnKind {
+  run
+    .column_kind
+    .as_ref()
+    .and_then(|kind| kind.as_str())
+    .and_then(|kind| ResultColumnKind::from_str(kind).ok())
+    .unwrap_or(ResultColumnKind::Utf16CodeUnits)
+}
+
+// Returns the number of columns `c` spans
+fn width(c: char, column_kind: ResultColumnKind) -> i64 {
+  match column_kind {
+    ResultColumnKind::Utf16CodeUnits => c.len_utf16() as i64,
+    ResultColumnKind::UnicodeCodePoints => 1,
+  }
+}
+
+// Returns the byte offset of the 1-based `column` of `line`, where a column
+// one past the last character is the end of the line
+fn column_offset(
+  line: &str,
+  column: i64,
+  column_kind: ResultColumnKind,
+) -> Option<usize> {
+  let mut units = 1;
+  for (offset, c) in line.char_indices() {
+    if units == column {
+      return Some(offset);
+    }
+    units += width(c, column_kind);
+  }
+  (units == column).then_some(line.len())
+}
+
+// Returns the byte offset of character `offset`
+fn char_offset(contents: &str, offset: usize) -> Option<usize> {
+  contents
+    .char_indices()
+    .map(|(index, _)| index)
+    .chain(std::iter::once(contents.len()))
+    .nth(offset)
+}
+
+/// Returns the byte range of `region` in `contents`
+///
+/// The region is resolved from its `byteOffset` and `byteLength` if present,
+/// else from its `charOffset` and `charLength`, else from its lines and
+/// columns, with the defaults of §3.30 of the SARIF specification: a region
+/// without `startColumn` starts at the beginning of its line, and one without
+/// `endColumn` ends at the end of its `endLine` (which defaults to
+/// `startLine`), excluding the line terminator. Returns `None` if the region
+/// is outside of `contents` or does not fall on character boundaries.
+///
+/// # Arguments
+///
+/// * `contents` - The text the region refers to
+/// * `region` - The region to resolve
+/// * `column_kind` - The unit in which columns are counted, see [column_kind]
+pub fn byte_range(
+  contents: &str,
+  region: &sarif::Region,
+  column_kind: ResultColumnKind,
+) -> Option<Range<usize>> {
+  let range = if let Some(offset) = region.byte_offset {
+    let start = usize::try_from(offset).ok()?;
+    start..start + usize::try_from(region.byte_length.unwrap_or(0)).ok()?
+  } else if let Some(offset) = region.char_offset {
+    let start = usize::try_from(offset).ok()?;
+    let length = usize::try_from(region.char_length.unwrap_or(0)).ok()?;
+    char_offset(contents, start)?..char_offset(contents, start + length)?
+  } else {
+    let start_line = usize::try_from(region.start_line?).ok()?;
+    let end_line = region
+      .end_line
+      .map_or(Some(start_line), |line| usize::try_from(line).ok())?;
+    // the byte offset of every line, without its line terminator
+    let lines: Vec<(usize, &str)> = contents
+      .split_inclusive('\n')
+      .scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line.trim_end_matches(['\r', '\n'])))
+      })
+      .collect();
+    let (start_offset, start) = lines.get(start_line.checked_sub(1)?)?;
+    let (end_offset, end) = lines.get(end_line.checked_sub(1)?)?;
+    let end_column = region.end_column.unwrap_or_else(|| {
+      end.chars().map(|c| width(c, column_kind)).sum::<i64>() + 1
+    });
+    let start_column = region.start_column.unwrap_or(1);
+    start_offset + column_offset(start, start_column, column_kind)?
+      ..end_offset + column_offset(end, end_column, column_kind)?
+  };
+  (range.start <= range.end
+    && contents.is_char_boundary(range.start)
+    && contents.is_char_boundary(range.end))
+  .then_some(range)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn region(json: &str) -> sarif::Region {
+    serde_json::from_str(json).unwrap()
+  }
+
+  #[test]
+  fn test_byte_range_columns() {
+    let contents = "fn main() {\r\n  let s = \"𝄞é\"; // x\n}";
+    let utf16 = ResultColumnKind::Utf16CodeUnits;
+    let code_points = ResultColumnKind::UnicodeCodePoints;
+    let slice = |json: &str, column_kind| {
+      byte_range(contents, &region(json), column_kind)
+        .map(|range| &contents[range])
+    };
+
+    // 𝄞 spans two UTF-16 code units but a single code point
+    let json = r#"{"startLine":2,"startColumn":14,"endColumn":15}"#;
+    assert_eq!(slice(json, utf16), Some("é"));
+    assert_eq!(slice(json, code_points), Some("\""));
+    // a column in the middle of a surrogate pair is not a character boundary
+    let json = r#"{"startLine":2,"startColumn":13,"endColumn":15}"#;
+    assert_eq!(slice(json, utf16), None);
+    // the end of the line excludes its terminator
+    let json = r#"{"startLine":1,"startColumn":11}"#;
+    assert_eq!(slice(json, utf16), Some("{"));
+    let json = r#"{"startLine":2,"endLine":3,"endColumn":2}"#;
+    assert_eq!(slice(json, code_points), Some("  let s = \"𝄞é\"; // x\n}"));
+    let json = r#"{"startLine":4}"#;
+    assert_eq!(slice(json, utf16), None);
+  }
+
+  #[test]
+  fn test_byte_range_offsets() {
+    let contents = "é𝄞x";
+    let utf16 = ResultColumnKind::Utf16CodeUnits;
+    let range = |json: &str| byte_range(contents, &region(json), utf16);
+
+    assert_eq!(range(r#"{"charOffset":1,"charLength":1}"#), Some(2..6));
+    assert_eq!(range(r#"{"charOffset":3}"#), Some(7..7));
+    assert_eq!(range(r#"{"charOffset":4}"#), None);
+    assert_eq!(range(r#"{"byteOffset":2,"byteLength":4}"#), Some(2..6));
+    assert_eq!(range(r#"{"byteOffset":1,"byteLength":1}"#), None);
+  }
+
+  #[test]
+  fn test_column_kind() {
+    let run: sarif::Run = serde_json::from_str(
+      r#"{"tool":{"driver":{"name":"clippy"}},"columnKind":"unicodeCodePoints"}"#,
+    )
+    .unwrap();
+    assert_eq!(column_kind(&run), ResultColumnKind::UnicodeCodePoints);
+    let run: sarif::Run =
+      serde_json::from_str(r#"{"tool":{"driver":{"name":"clippy"}}}"#).unwrap();
+    assert_eq!(column_kind(&run), ResultColumnKind::Utf16CodeUnits);
+  }
+}
@@ -107,7 +107,9 @@ pub enum ResultLevel {
 }
 
 #[doc = "The state of a result relative to a baseline of a previous run."]
-#[derive(Display, Debug, Serialize, Deserialize, EnumString)]
+#[derive(
+  Clone, Copy, Display, Debug, Serialize, Deserialize, EnumString, PartialEq,
+)]
 #[serde(rename_all = "camelCase")]
 #[strum(serialize_all = "camelCase")]
 pub enum ResultBaselineState {
@@ -118,7 +120,9 @@ pub enum ResultBaselineState {
 }
 
 #[doc = "Specifies the unit in which the tool measures columns."]
-#[derive(Display, Debug, Serialize, Deserialize, EnumString)]
+#[derive(
+  Clone, Copy, Display, Debug, Serialize, Deserialize, EnumString, PartialEq,
+)]
 #[serde(rename_all = "camelCase")]
 #[strum(serialize_all = "camelCase")]
 pub enum ResultColumnKind {
@@ -145,12 +149,15 @@ pub enum SupressionStatus {
 }
 
 #[doc = "Specifies the importance of this location in understanding the code flow in which it occurs. The order from most to least important is \"essential\", \"important\", \"unimportant\". Default: \"important\"."]
-#[derive(Display, Debug, Serialize, Deserialize, EnumString)]
+#[derive(
+  Clone, Copy, Display, Debug, Serialize, Deserialize, EnumString, PartialEq,
+)]
 #[serde(rename_all = "camelCase")]
 #[strum(serialize_all = "camelCase")]
 pub enum ThreadFlowLocationImportance {
   Important,
   Essential,
+  Unimportant,
 }
 
 #[doc = "The kinds of data contained in this object."]
@@ -263,4 +270,10 @@ mod tests {
     let v = ResultColumnKind::from_str("utf16CodeUnits").unwrap();
     assert!(matches!(v, ResultColumnKind::Utf16CodeUnits));
   }
+
+  #[test]
+  fn test_parse_unimportant() {
+    let v = ThreadFlowLocationImportance::from_str("unimportant").unwrap();
+    assert!(matches!(v, ThreadFlowLocationImportance::Unimportant));
+  }
 }
@@ -0,0 +1,603 @@
+//! Reads and writes the results of a SARIF log one at a time.
+//!
+//! Deserializing a [crate::sarif::Sarif] materializes every result of every
+//! run, which does not scale to logs of hundreds of megabytes.
+//! [for_each_result] instead deserializes a single [crate::sarif::Result] at a
+//! time and hands it to a callback, along with the run it belongs to (without
+//! its results), so memory use is bounded by the size of the largest result and
+//! of the run level data (ex. rules and artifacts).
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_sarif::stream::for_each_result;
+//! use std::io::Cursor;
+//!
+//! let log = r#"{ "version": "2.1.0", "runs": [{
+//!   "results": [{ "message": { "text": "a" } }, { "message": { "text": "b" } }],
+//!   "tool": { "driver": { "name": "clippy" } }
+//! }] }"#;
+//!
+//! let mut messages = vec![];
+//! for_each_result(Cursor::new(log), |_, run, result| -> std::io::Result<()> {
+//!   assert_eq!(run.tool.driver.name, "clippy");
+//!   messages.push(result.message.text.unwrap());
+//!   Ok(())
+//! })
+//! .unwrap();
+//! assert_eq!(messages, vec!["a", "b"]);
+//! ```
+//!
+//! Conversely, [SarifWriter] writes a log with a single run as its results are
+//! produced, so a converter never has to hold them in memory:
+//!
+//! ```rust
+//! use serde_sarif::sarif::{Message, Result, Run, Tool, ToolComponent};
+//! use serde_sarif::stream::SarifWriter;
+//! use std::io::Cursor;
+//!
+//! let mut writer = SarifWriter::new(vec![], Cursor::new(vec![])).unwrap();
+//! writer
+//!   .write_result(
+//!     &Result::builder()
+//!       .message(Message::builder().text("a").build())
+//!       .build(),
+//!   )
+//!   .unwrap();
+//! let run = Run::builder()
+//!   .tool(Tool::builder().driver(ToolComponent::builder().name("clippy").build()).build())
+//!   .build();
+//! let log = writer.finish(&run).unwrap();
+//!
+//! let sarif: serde_sarif::sarif::Sarif = serde_json::from_slice(&log).unwrap();
+//! assert_eq!(sarif.runs[0].results.as_ref().unwrap().len(), 1);
+//! ```
+
+use std::fmt;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+use serde::de::{
+  self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess,
+  Visitor,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::sarif;
+
+// A run deserialized without its results
+struct RunMetadata(sarif::Run);
+
+impl<'de> Deserialize<'de> for RunMetadata {
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    struct RunMetadataVisitor;
+
+    impl<'de> Visitor<'de> for RunMetadataVisitor {
+      type Value = RunMetadata;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a SARIF run")
+      }
+
+      fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+      ) -> Result<Self::Value, A::Error> {
+        let mut run = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+          if key == "results" {
+            map.next_value::<IgnoredAny>()?;
+          } else {
+            run.insert(key, map.next_value::<Value>()?);
+          }
+        }
+        serde_json::from_value(Value::Object(run))
+          .map(RunMetadata)
+          .map_err(de::Error::custom)
+      }
+    }
+
+    deserializer.deserialize_map(RunMetadataVisitor)
+  }
+}
+
+// The runs of a log deserialized without their results
+struct LogMetadata(Vec<sarif::Run>);
+
+impl<'de> Deserialize<'de> for LogMetadata {
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    struct LogMetadataVisitor;
+
+    impl<'de> Visitor<'de> for LogMetadataVisitor {
+      type Value = LogMetadata;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a SARIF log")
+      }
+
+      fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+      ) -> Result<Self::Value, A::Error> {
+        let mut runs = None;
+        while let Some(key) = map.next_key::<String>()? {
+          if key == "runs" {
+            let metadata: Vec<RunMetadata> = map.next_value()?;
+            runs = Some(metadata.into_iter().map(|run| run.0).collect());
+          } else {
+            map.next_value::<IgnoredAny>()?;
+          }
+        }
+        runs
+          .map(LogMetadata)
+          .ok_or_else(|| de::Error::missing_field("runs"))
+      }
+    }
+
+    deserializer.deserialize_map(LogMetadataVisitor)
+  }
+}
+
+// State shared by the seeds which stream the results of a log
+struct Context<'a, F, E> {
+  runs: &'a [sarif::Run],
+  f: F,
+  // the error returned by `f`, which aborted the deserialization
+  error: Option<E>,
+}
+
+impl<F, E> Context<'_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  // Calls `f`, keeping its error to return it once the deserialization is
+  // aborted
+  fn call<Err: de::Error>(
+    &mut self,
+    index: usize,
+    run: &sarif::Run,
+    result: sarif::Result,
+  ) -> Result<(), Err> {
+    (self.f)(index, run, result).map_err(|error| {
+      self.error = Some(error);
+      Err::custom("aborted")
+    })
+  }
+}
+
+// The seeds below walk the log down to the `results` arrays of its runs,
+// skipping every other property (which is already known from the metadata).
+struct LogSeed<'c, 'a, F, E>(&'c mut Context<'a, F, E>);
+struct RunsSeed<'c, 'a, F, E>(&'c mut Context<'a, F, E>);
+struct RunSeed<'c, 'a, F, E>(&'c mut Context<'a, F, E>, usize);
+struct ResultsSeed<'c, 'a, 'r, F, E>(
+  &'c mut Context<'a, F, E>,
+  usize,
+  &'r sarif::Run,
+);
+
+impl<'de, F, E> DeserializeSeed<'de> for LogSeed<'_, '_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  type Value = ();
+
+  fn deserialize<D: Deserializer<'de>>(
+    self,
+    deserializer: D,
+  ) -> Result<(), D::Error> {
+    deserializer.deserialize_map(self)
+  }
+}
+
+impl<'de, F, E> Visitor<'de> for LogSeed<'_, '_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  type Value = ();
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a SARIF log")
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+    while let Some(key) = map.next_key::<String>()? {
+      if key == "runs" {
+        map.next_value_seed(RunsSeed(&mut *self.0))?;
+      } else {
+        map.next_value::<IgnoredAny>()?;
+      }
+    }
+    Ok(())
+  }
+}
+
+impl<'de, F, E> DeserializeSeed<'de> for RunsSeed<'_, '_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  type Value = ();
+
+  fn deserialize<D: Deserializer<'de>>(
+    self,
+    deserializer: D,
+  ) -> Result<(), D::Error> {
+    deserializer.deserialize_seq(self)
+  }
+}
+
+impl<'de, F, E> Visitor<'de> for RunsSeed<'_, '_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  type Value = ();
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("an array of SARIF runs")
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+    let mut index = 0;
+    while seq
+      .next_element_seed(RunSeed(&mut *self.0, index))?
+      .is_some()
+    {
+      index += 1;
+    }
+    Ok(())
+  }
+}
+
+impl<'de, F, E> DeserializeSeed<'de> for RunSeed<'_, '_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  type Value = ();
+
+  fn deserialize<D: Deserializer<'de>>(
+    self,
+    deserializer: D,
+  ) -> Result<(), D::Error> {
+    deserializer.deserialize_map(self)
+  }
+}
+
+impl<'de, F, E> Visitor<'de> for RunSeed<'_, '_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  type Value = ();
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a SARIF run")
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+    let Self(context, index) = self;
+    let runs = context.runs;
+    let run = runs.get(index).ok_or_else(|| {
+      de::Error::custom("the log changed while it was being read")
+    })?;
+    while let Some(key) = map.next_key::<String>()? {
+      if key == "results" {
+        map.next_value_seed(ResultsSeed(&mut *context, index, run))?;
+      } else {
+        map.next_value::<IgnoredAny>()?;
+      }
+    }
+    Ok(())
+  }
+}
+
+impl<'de, F, E> DeserializeSeed<'de> for ResultsSeed<'_, '_, '_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  type Value = ();
+
+  fn deserialize<D: Deserializer<'de>>(
+    self,
+    deserializer: D,
+  ) -> Result<(), D::Error> {
+    deserializer.deserialize_option(self)
+  }
+}
+
+impl<'de, F, E> Visitor<'de> for ResultsSeed<'_, '_, '_, F, E>
+where
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+{
+  type Value = ();
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("an array of SARIF results")
+  }
+
+  fn visit_none<Err: de::Error>(self) -> Result<(), Err> {
+    Ok(())
+  }
+
+  fn visit_some<D: Deserializer<'de>>(
+    self,
+    deserializer: D,
+  ) -> Result<(), D::Error> {
+    deserializer.deserialize_seq(self)
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+    let Self(context, index, run) = self;
+    while let Some(result) = seq.next_element::<sarif::Result>()? {
+      context.call(index, run, result)?;
+    }
+    Ok(())
+  }
+}
+
+/// Returns the runs of the SARIF log read from `reader`, without their results
+///
+/// Only the run level properties (ex. `tool` and its rules) are held in
+/// memory. `reader` is sought back to the position it started at, so that the
+/// results of the log can be streamed next with [for_each_result].
+///
+/// # Arguments
+///
+/// * `reader` - A seekable reader of a SARIF log
+pub fn read_runs<R: Read + Seek>(
+  mut reader: R,
+) -> serde_json::Result<Vec<sarif::Run>> {
+  let start = reader.stream_position().map_err(serde_json::Error::io)?;
+  let mut deserializer =
+    serde_json::Deserializer::from_reader(BufReader::new(&mut reader));
+  let LogMetadata(runs) = LogMetadata::deserialize(&mut deserializer)?;
+  deserializer.end()?;
+  reader
+    .seek(SeekFrom::Start(start))
+    .map_err(serde_json::Error::io)?;
+  Ok(runs)
+}
+
+/// Calls `f` with every result of the SARIF log read from `reader`, in order,
+/// and returns the runs of the log without their results
+///
+/// `f` is called with the index of the run the result belongs to, the run
+/// itself without its `results`, and the result. Only a single result is held
+/// in memory at a time. Because run level properties (ex. `tool`) may appear
+/// after `results`, the log is read twice: once to collect the runs without
+/// their results, and once more, after seeking back to the position `reader`
+/// started at, to stream the results.
+///
+/// The first error returned by `f` stops the iteration and is returned.
+///
+/// # Arguments
+///
+/// * `reader` - A seekable reader of a SARIF log
+/// * `f` - A function called with every result
+pub fn for_each_result<R, F, E>(
+  mut reader: R,
+  f: F,
+) -> Result<Vec<sarif::Run>, E>
+where
+  R: Read + Seek,
+  F: FnMut(usize, &sarif::Run, sarif::Result) -> Result<(), E>,
+  E: From<serde_json::Error> + From<std::io::Error>,
+{
+  let runs = read_runs(&mut reader)?;
+  let mut context = Context {
+    runs: &runs,
+    f,
+    error: None,
+  };
+  let mut deserializer =
+    serde_json::Deserializer::from_reader(BufReader::new(&mut reader));
+  match LogSeed(&mut context).deserialize(&mut deserializer) {
+    Ok(()) => Ok(runs),
+    Err(error) => Err(context.error.take().unwrap_or_else(|| error.into())),
+  }
+}
+
+// Returns `value` pretty printed, with every line but the first indented by
+// `depth` levels. JSON strings cannot contain raw newlines, so every newline
+// is a line break of the formatting.
+fn to_string_indented<T: Serialize>(
+  value: &T,
+  depth: usize,
+) -> serde_json::Result<String> {
+  let indent = format!("\n{}", "  ".repeat(depth));
+  Ok(serde_json::to_string_pretty(value)?.replace('\n', &indent))
+}
+
+/// Writes a SARIF log with a single run, one result at a time
+///
+/// [SarifWriter::new] writes the header of the log, [SarifWriter::write_result]
+/// appends results to a spool as they are produced, and [SarifWriter::finish]
+/// writes the members of the run followed by the spooled results. Only a
+/// single result is held in memory at a time.
+///
+/// The output is pretty printed like `serde_json::to_writer_pretty`, and its
+/// members are written in this order:
+///
+/// 1. `$schema` and `version`, so that consumers can identify the log before
+///    any result
+/// 2. every member of the run other than `results`, including `tool`, whose
+///    rules are only known once a converter has seen every result
+/// 3. `runs[0].results`, as they were written
+///
+/// The metadata of the run thus precedes its results, as most tools write it.
+pub struct SarifWriter<W: Write, S: Read + Write + Seek> {
+  writer: W,
+  spool: BufWriter<S>,
+  results: usize,
+}
+
+impl<W: Write, S: Read + Write + Seek> SarifWriter<W, S> {
+  /// Returns a writer which has written the header of the log to `writer`
+  ///
+  /// # Arguments
+  ///
+  /// * `writer` - A `Writer` to write the log to
+  /// * `spool` - An empty buffer (ex. a temporary file) holding the results
+  ///   until the run is finished
+  pub fn new(mut writer: W, spool: S) -> io::Result<Self> {
+    write!(
+      writer,
+      "{{\n  \"$schema\": {},\n  \"version\": {},\n  \"runs\": [\n    {{",
+      serde_json::to_string(sarif::SCHEMA_URL)?,
+      serde_json::to_string(&sarif::Version::V2_1_0)?
+    )?;
+    Ok(Self {
+      writer,
+      spool: BufWriter::new(spool),
+      results: 0,
+    })
+  }
+
+  /// Appends a result to the run
+  ///
+  /// # Arguments
+  ///
+  /// * `result` - The result to write
+  pub fn write_result(&mut self, result: &sarif::Result) -> io::Result<()> {
+    let separator = if self.results == 0 { "" } else { "," };
+    write!(
+      self.spool,
+      "{}\n        {}",
+      separator,
+      to_string_indented(result, 4)?
+    )?;
+    self.results += 1;
+    Ok(())
+  }
+
+  /// Returns the underlying writer, after writing the members of `run` other
+  /// than `results`, the spooled results and the end of the log
+  ///
+  /// # Arguments
+  ///
+  /// * `run` - The run the results belong to; its `results` are ignored
+  pub fn finish(mut self, run: &sarif::Run) -> io::Result<W> {
+    if let Value::Object(run) = serde_json::to_value(run)? {
+      for (key, value) in run.iter().filter(|(key, _)| *key != "results") {
+        write!(
+          self.writer,
+          "\n      {}: {},",
+          serde_json::to_string(key)?,
+          to_string_indented(value, 3)?
+        )?;
+      }
+    }
+    write!(self.writer, "\n      \"results\": [")?;
+    let mut spool = self
+      .spool
+      .into_inner()
+      .map_err(|error| error.into_error())?;
+    spool.rewind()?;
+    io::copy(&mut spool, &mut self.writer)?;
+    if self.results > 0 {
+      write!(self.writer, "\n      ")?;
+    }
+    write!(self.writer, "]\n    }}\n  ]\n}}")?;
+    self.writer.flush()?;
+    Ok(self.writer)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Cursor;
+
+  const LOG: &str = r#"{
+    "runs": [
+      {
+        "results": [{ "message": { "text": "1" } }, { "message": { "text": "2" } }],
+        "tool": { "driver": { "name": "a", "rules": [{ "id": "r" }] } }
+      },
+      { "tool": { "driver": { "name": "b" } } },
+      {
+        "tool": { "driver": { "name": "c" } },
+        "results": [{ "message": { "text": "3" } }]
+      }
+    ],
+    "version": "2.1.0"
+  }"#;
+
+  #[test]
+  fn test_for_each_result() {
+    let mut results = vec![];
+    for_each_result(Cursor::new(LOG), |index, run, result| {
+      assert!(run.results.is_none());
+      results.push((
+        index,
+        run.tool.driver.name.clone(),
+        run.tool.driver.rules.as_ref().map_or(0, Vec::len),
+        result.message.text.unwrap(),
+      ));
+      Ok::<(), std::io::Error>(())
+    })
+    .unwrap();
+
+    assert_eq!(
+      results,
+      vec![
+        (0, "a".to_string(), 1, "1".to_string()),
+        (0, "a".to_string(), 1, "2".to_string()),
+        (2, "c".to_string(), 0, "3".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_read_runs() {
+    let mut reader = Cursor::new(LOG);
+    let runs = read_runs(&mut reader).unwrap();
+    let names: Vec<&str> = runs
+      .iter()
+      .map(|run| run.tool.driver.name.as_str())
+      .collect();
+    assert_eq!(names, vec!["a", "b", "c"]);
+    assert!(runs.iter().all(|run| run.results.is_none()));
+    assert_eq!(reader.position(), 0);
+  }
+
+  #[test]
+  fn test_for_each_result_stops_on_error() {
+    let mut count = 0;
+    let error = for_each_result(Cursor::new(LOG), |_, _, _| {
+      count += 1;
+      Err(std::io::Error::other("stop"))
+    })
+    .unwrap_err();
+    assert_eq!(error.to_string(), "stop");
+    assert_eq!(count, 1);
+  }
+
+  #[test]
+  fn test_sarif_writer() {
+    let sarif: sarif::Sarif = serde_json::from_str(&format!(
+      r#"{{"$schema":"{}","version":"2.1.0","runs":[{{
+        "results":[{{"ruleId":"a","message":{{"text":"1"}},"locations":[{{"physicalLocation":{{"artifactLocation":{{"uri":"lib.rs"}}}}}}]}},{{"message":{{"text":"2"}}}}],
+        "tool":{{"driver":{{"name":"clippy","rules":[{{"id":"a"}}]}}}}
+      }}]}}"#,
+      sarif::SCHEMA_URL
+    ))
+    .unwrap();
+
+    let run = &sarif.runs[0];
+    let mut writer = SarifWriter::new(vec![], Cursor::new(vec![])).unwrap();
+    run
+      .results
+      .iter()
+      .flatten()
+      .for_each(|result| writer.write_result(result).unwrap());
+    let log = String::from_utf8(writer.finish(run).unwrap()).unwrap();
+
+    assert_eq!(serde_json::from_str::<sarif::Sarif>(&log).unwrap(), sarif);
+    let position = |member: &str| log.find(&format!("\"{}\":", member));
+    assert!(position("version") < position("tool"));
+    assert!(position("tool") < position("results"));
+  }
+}
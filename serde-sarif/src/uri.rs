@@ -0,0 +1,552 @@
+//! Converts between file paths and the URIs of [sarif::ArtifactLocation]s.
+//!
+//! Tools report files as they were passed to them, which makes their paths
+//! either absolute or relative to the directory the tool ran in. Neither can
+//! be matched to a checkout of the same sources in another directory (ex. by
+//! GitHub code scanning). [normalize_uris] instead makes every URI relative to
+//! the root of the sources, which it records as the `%SRCROOT%` base URI of
+//! the run, and percent-encodes it as required by RFC 3986. [resolve] goes the
+//! other way, and resolves a relative URI against its (possibly chained) base
+//! URIs to find the file it refers to.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_sarif::sarif::Sarif;
+//! use serde_sarif::uri::{normalize_uris, SRCROOT};
+//! use std::path::Path;
+//!
+//! let mut sarif: Sarif = serde_json::from_str(
+//!   r#"{ "version": "2.1.0", "runs": [{ "tool": { "driver": { "name": "clang-tidy" } }, "results": [{
+//!     "message": { "text": "narrowing conversion" },
+//!     "locations": [{ "physicalLocation": { "artifactLocation": { "uri": "/src/my project/main.cpp" } } }]
+//!   }] }] }"#
+//! ).unwrap();
+//!
+//! normalize_uris(&mut sarif, Path::new("/src/my project"));
+//! let artifact_location = sarif.runs[0].results.as_ref().unwrap()[0]
+//!   .locations.as_ref().unwrap()[0]
+//!   .physical_location.as_ref().unwrap()
+//!   .artifact_location.as_ref().unwrap();
+//! assert_eq!(artifact_location.uri.as_deref(), Some("main.cpp"));
+//! assert_eq!(artifact_location.uri_base_id.as_deref(), Some(SRCROOT));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use thiserror::Error;
+
+use crate::{sarif, visit};
+
+/// The base URI id which relative URIs are resolved against, defined in
+/// `run.originalUriBaseIds` as the root of the sources
+pub const SRCROOT: &str = "%SRCROOT%";
+
+// Returns `value` with every byte but the unreserved characters of RFC 3986
+// and those of `keep` percent-encoded
+fn percent_encode(value: &str, keep: &[u8]) -> String {
+  value
+    .bytes()
+    .map(|byte| {
+      if byte.is_ascii_alphanumeric()
+        || b"-._~".contains(&byte)
+        || keep.contains(&byte)
+      {
+        (byte as char).to_string()
+      } else {
+        format!("%{:02X}", byte)
+      }
+    })
+    .collect()
+}
+
+// Returns `value` with its percent-encoded bytes decoded
+fn percent_decode(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let byte = (bytes[i] == b'%')
+      .then(|| value.get(i + 1..i + 3))
+      .flatten()
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match byte {
+      Some(byte) => {
+        decoded.push(byte);
+        i += 3;
+      }
+      None => {
+        decoded.push(bytes[i]);
+        i += 1;
+      }
+    }
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Returns the `/` separated segments of `path`
+fn segments(path: &Path) -> String {
+  path
+    .components()
+    .filter_map(|component| match component {
+      Component::Normal(segment) => Some(segment.to_string_lossy()),
+      _ => None,
+    })
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+/// Returns the percent-encoded URI reference of `path`
+///
+/// Relative paths are converted into relative references (ex.
+/// `src/my%20file.rs`), and absolute paths into `file://` URIs (ex.
+/// `file:///home/me/src/main.rs`).
+///
+/// # Arguments
+///
+/// * `path` - The path to convert
+pub fn from_path(path: &Path) -> String {
+  if !path.is_absolute() {
+    return percent_encode(&segments(path), b"/");
+  }
+  let prefix = match path.components().next() {
+    Some(Component::Prefix(prefix)) => {
+      format!("/{}", prefix.as_os_str().to_string_lossy())
+    }
+    _ => String::new(),
+  };
+  format!(
+    "file://{}/{}",
+    percent_encode(&prefix, b"/:"),
+    percent_encode(&segments(path), b"/")
+  )
+}
+
+/// Returns the path of the file `uri` refers to
+///
+/// `file://` URIs and relative references are percent-decoded, while URIs
+/// without a scheme which are not valid references (ex. `50%.rs`) are treated
+/// as plain paths, as reported by most tools.
+///
+/// # Arguments
+///
+/// * `uri` - The URI (or path) to convert
+pub fn to_path(uri: &str) -> PathBuf {
+  if let Some(path) = uri.strip_prefix("file://") {
+    // file:///C:/src -> C:/src
+    let path = path.strip_prefix("localhost").unwrap_or(path);
+    let decoded = percent_decode(path);
+    let windows_drive = decoded.as_bytes().get(2) == Some(&b':')
+      && decoded.starts_with('/')
+      && decoded.as_bytes()[1].is_ascii_alphabetic();
+    return PathBuf::from(if windows_drive {
+      &decoded[1..]
+    } else {
+      &decoded
+    });
+  }
+  let encoded = uri.as_bytes().windows(3).any(|window| {
+    window[0] == b'%'
+      && window[1].is_ascii_hexdigit()
+      && window[2].is_ascii_hexdigit()
+  });
+  PathBuf::from(if encoded {
+    percent_decode(uri)
+  } else {
+    uri.to_string()
+  })
+}
+
+/// Errors which can occur when resolving the URI of an artifact location
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ResolveError {
+  #[error("artifact location has no uri")]
+  MissingUri,
+  #[error("uriBaseId {0} is not defined in originalUriBaseIds")]
+  UndefinedBaseId(String),
+  #[error("uriBaseId {0} is defined in terms of itself")]
+  Cycle(String),
+}
+
+// Returns whether `uri` starts with a scheme (ex. `file:`), which makes it an
+// absolute URI rather than a relative reference. Single letter schemes are
+// Windows drives (ex. `C:/src`) reported as plain paths.
+fn has_scheme(uri: &str) -> bool {
+  match uri.split_once(':') {
+    Some((scheme, _)) => {
+      scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+    }
+    None => false,
+  }
+}
+
+// Returns the reference `uri` resolved against the base URI `base`
+fn join(base: &str, uri: &str) -> String {
+  if has_scheme(uri) || base.is_empty() {
+    return uri.to_string();
+  }
+  if uri.starts_with('/') {
+    // an absolute path replaces the whole path of the base
+    let authority =
+      base.find("://").map(|start| start + 3).map_or(0, |start| {
+        base[start..]
+          .find('/')
+          .map_or(base.len(), |end| start + end)
+      });
+    return format!("{}{}", &base[..authority], uri);
+  }
+  let uri = uri.strip_prefix("./").unwrap_or(uri);
+  if base.ends_with('/') {
+    format!("{}{}", base, uri)
+  } else {
+    format!("{}/{}", base, uri)
+  }
+}
+
+// Resolves `artifact_location` against its base, `visited` holding the ids of
+// the bases being resolved to detect cycles
+fn resolve_with<'a>(
+  artifact_location: &'a sarif::ArtifactLocation,
+  original_uri_base_ids: Option<&'a BTreeMap<String, sarif::ArtifactLocation>>,
+  visited: &mut Vec<&'a str>,
+) -> Result<String, ResolveError> {
+  let uri = artifact_location.uri.as_deref().unwrap_or_default();
+  let uri_base_id = match artifact_location.uri_base_id.as_deref() {
+    Some(uri_base_id) if !has_scheme(uri) => uri_base_id,
+    _ if artifact_location.uri.is_some() => return Ok(uri.to_string()),
+    _ => return Err(ResolveError::MissingUri),
+  };
+  if visited.contains(&uri_base_id) {
+    return Err(ResolveError::Cycle(uri_base_id.to_string()));
+  }
+  let base = original_uri_base_ids
+    .and_then(|base_ids| base_ids.get(uri_base_id))
+    .ok_or_else(|| ResolveError::UndefinedBaseId(uri_base_id.to_string()))?;
+  visited.push(uri_base_id);
+  // a base without a uri is only a description of an otherwise unknown root
+  let base = match base.uri {
+    Some(_) => resolve_with(base, original_uri_base_ids, visited)?,
+    None => return Err(ResolveError::UndefinedBaseId(uri_base_id.to_string())),
+  };
+  Ok(join(&base, uri))
+}
+
+/// Returns the URI of `artifact_location` resolved against its `uriBaseId`, as
+/// described in §3.14.14 of the SARIF specification
+///
+/// The base of a `uriBaseId` is looked up in `original_uri_base_ids`, and is
+/// itself resolved against its own `uriBaseId`, recursively. The result is
+/// usually an absolute URI, but remains a relative reference when the outermost
+/// base is relative (ex. to the current directory).
+///
+/// # Arguments
+///
+/// * `artifact_location` - The artifact location to resolve
+/// * `original_uri_base_ids` - The `originalUriBaseIds` of its run
+pub fn resolve(
+  artifact_location: &sarif::ArtifactLocation,
+  original_uri_base_ids: Option<&BTreeMap<String, sarif::ArtifactLocation>>,
+) -> Result<String, ResolveError> {
+  resolve_with(artifact_location, original_uri_base_ids, &mut Vec::new())
+}
+
+/// Returns the path of the file `artifact_location` refers to
+///
+/// Its URI is [resolve]d, and converted into a path with [to_path]. A URI
+/// whose `uriBaseId` is not defined is assumed to be relative to the current
+/// directory, as many logs are consumed from the root of their sources.
+///
+/// # Arguments
+///
+/// * `artifact_location` - The artifact location to resolve
+/// * `original_uri_base_ids` - The `originalUriBaseIds` of its run
+pub fn resolve_path(
+  artifact_location: &sarif::ArtifactLocation,
+  original_uri_base_ids: Option<&BTreeMap<String, sarif::ArtifactLocation>>,
+) -> Result<PathBuf, ResolveError> {
+  match resolve(artifact_location, original_uri_base_ids) {
+    Ok(uri) => Ok(to_path(&uri)),
+    Err(ResolveError::UndefinedBaseId(_)) => Ok(to_path(
+      artifact_location.uri.as_deref().unwrap_or_default(),
+    )),
+    Err(error) => Err(error),
+  }
+}
+
+// Returns `path` made absolute and with `.` and `..` removed, resolving
+// symbolic links when the file exists
+fn absolute(path: &Path) -> Option<PathBuf> {
+  let path = std::env::current_dir().ok()?.join(path);
+  if let Ok(path) = fs::canonicalize(&path) {
+    return Some(path);
+  }
+  let mut normalized = PathBuf::new();
+  path.components().for_each(|component| match component {
+    Component::CurDir => {}
+    Component::ParentDir => {
+      normalized.pop();
+    }
+    component => normalized.push(component),
+  });
+  Some(normalized)
+}
+
+/// Returns the default root of the sources: the top level directory of the
+/// git repository containing the current directory, or the current directory
+/// if it is not in a git repository
+pub fn default_src_root() -> io::Result<PathBuf> {
+  let output = Command::new("git")
+    .args(["rev-parse", "--show-toplevel"])
+    .output();
+  match output {
+    Ok(output) if output.status.success() => Ok(PathBuf::from(
+      String::from_utf8_lossy(&output.stdout).trim_end(),
+    )),
+    _ => std::env::current_dir(),
+  }
+}
+
+// Rewrites artifact locations relative to the root of the sources, keeping
+// track of whether any of them refers to it
+pub(crate) struct UriNormalizer {
+  src_root: PathBuf,
+  relative: bool,
+}
+
+impl UriNormalizer {
+  pub(crate) fn new(src_root: &Path) -> Self {
+    Self {
+      src_root: absolute(src_root).unwrap_or_else(|| src_root.to_path_buf()),
+      relative: false,
+    }
+  }
+
+  // Rewrites `artifact_location` unless it is already relative to a base URI
+  pub(crate) fn normalize(
+    &mut self,
+    artifact_location: &mut sarif::ArtifactLocation,
+  ) {
+    if artifact_location.uri_base_id.is_some() {
+      return;
+    }
+    let path = match artifact_location.uri.as_deref() {
+      Some(uri) if uri.starts_with("file:") => to_path(uri),
+      Some(uri) if !uri.contains("://") => PathBuf::from(uri),
+      _ => return,
+    };
+    let path = match absolute(&path) {
+      Some(path) => path,
+      None => return,
+    };
+    match path.strip_prefix(&self.src_root) {
+      Ok(relative) => {
+        artifact_location.uri = Some(from_path(relative));
+        artifact_location.uri_base_id = Some(SRCROOT.to_string());
+        self.relative = true;
+      }
+      Err(_) => artifact_location.uri = Some(from_path(&path)),
+    }
+  }
+
+  // Returns the `originalUriBaseIds` defining the root of the sources, which
+  // the rewritten locations are relative to
+  pub(crate) fn original_uri_base_ids(
+    &self,
+  ) -> BTreeMap<String, sarif::ArtifactLocation> {
+    let mut uri = from_path(&self.src_root);
+    if !uri.ends_with('/') {
+      uri.push('/');
+    }
+    BTreeMap::from([(
+      SRCROOT.to_string(),
+      sarif::ArtifactLocation::builder().uri(uri).build(),
+    )])
+  }
+
+  pub(crate) fn normalize_result(&mut self, result: &mut sarif::Result) {
+    visit::for_each_artifact_location_mut(result, &mut |artifact_location| {
+      self.normalize(artifact_location)
+    });
+  }
+
+  // Rewrites the locations of the artifacts of `run`, and defines the root of
+  // the sources in its `originalUriBaseIds` if any location refers to it
+  pub(crate) fn finish(mut self, run: &mut sarif::Run) {
+    run
+      .artifacts
+      .iter_mut()
+      .flatten()
+      .filter_map(|artifact| artifact.location.as_mut())
+      .for_each(|location| self.normalize(location));
+    if self.relative {
+      let base_ids = self.original_uri_base_ids();
+      run
+        .original_uri_base_ids
+        .get_or_insert_with(BTreeMap::new)
+        .extend(base_ids);
+    }
+  }
+}
+
+/// Rewrites the artifact URIs of every run of `sarif` relative to `src_root`
+///
+/// URIs of files under `src_root` are made relative to it, percent-encoded,
+/// and set the `uriBaseId` [SRCROOT], which is defined as the `file://` URI of
+/// `src_root` in `run.originalUriBaseIds`. URIs of other files are converted
+/// into absolute `file://` URIs. Relative paths are resolved against the
+/// current directory, and URIs which already have a `uriBaseId` are left
+/// untouched.
+///
+/// # Arguments
+///
+/// * `sarif` - The SARIF log whose URIs to rewrite
+/// * `src_root` - The root of the sources, see [default_src_root]
+pub fn normalize_uris(sarif: &mut sarif::Sarif, src_root: &Path) {
+  sarif.runs.iter_mut().for_each(|run| {
+    let mut normalizer = UriNormalizer::new(src_root);
+    run
+      .results
+      .iter_mut()
+      .flatten()
+      .for_each(|result| normalizer.normalize_result(result));
+    normalizer.finish(run);
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_from_path() {
+    assert_eq!(from_path(Path::new("src/my file.rs")), "src/my%20file.rs");
+    assert_eq!(from_path(Path::new("./a:b/c#d.rs")), "a%3Ab/c%23d.rs");
+    assert_eq!(
+      from_path(Path::new("/home/me/src/50%.rs")),
+      "file:///home/me/src/50%25.rs"
+    );
+  }
+
+  #[test]
+  fn test_to_path() {
+    assert_eq!(to_path("src/my%20file.rs"), Path::new("src/my file.rs"));
+    assert_eq!(to_path("src/50%.rs"), Path::new("src/50%.rs"));
+    assert_eq!(
+      to_path("file:///home/me/src/50%25.rs"),
+      Path::new("/home/me/src/50%.rs")
+    );
+    assert_eq!(
+      to_path("file:///C:/src/main.rs"),
+      Path::new("C:/src/main.rs")
+    );
+  }
+
+  #[test]
+  fn test_resolve() {
+    let base_ids: BTreeMap<String, sarif::ArtifactLocation> =
+      serde_json::from_str(
+        r#"{
+          "REPO": {"uri": "file:///work/my%20repo/"},
+          "SRCROOT": {"uri": "src/", "uriBaseId": "REPO"},
+          "BIN": {"uri": "bin"},
+          "UNKNOWN": {"description": {"text": "the build directory"}},
+          "A": {"uri": "a/", "uriBaseId": "B"},
+          "B": {"uri": "b/", "uriBaseId": "A"}
+        }"#,
+      )
+      .unwrap();
+    let location = |uri: &str, uri_base_id: &str| {
+      let mut location = sarif::ArtifactLocation::builder().uri(uri).build();
+      location.uri_base_id = Some(uri_base_id.to_string());
+      location
+    };
+
+    assert_eq!(
+      resolve(&location("main.rs", "SRCROOT"), Some(&base_ids)),
+      Ok("file:///work/my%20repo/src/main.rs".to_string())
+    );
+    assert_eq!(
+      resolve(&location("/etc/hosts", "REPO"), Some(&base_ids)),
+      Ok("file:///etc/hosts".to_string())
+    );
+    assert_eq!(
+      resolve(&location("tool", "BIN"), Some(&base_ids)),
+      Ok("bin/tool".to_string())
+    );
+    assert_eq!(
+      resolve(&location("file:///main.rs", "SRCROOT"), Some(&base_ids)),
+      Ok("file:///main.rs".to_string())
+    );
+    assert_eq!(
+      resolve(&location("main.rs", "A"), Some(&base_ids)),
+      Err(ResolveError::Cycle("A".to_string()))
+    );
+    assert_eq!(
+      resolve(&location("main.rs", "UNKNOWN"), Some(&base_ids)),
+      Err(ResolveError::UndefinedBaseId("UNKNOWN".to_string()))
+    );
+    assert_eq!(
+      resolve_path(&location("src/main.rs", "MISSING"), None),
+      Ok(PathBuf::from("src/main.rs"))
+    );
+    assert_eq!(
+      resolve_path(&location("main.rs", "SRCROOT"), Some(&base_ids)),
+      Ok(PathBuf::from("/work/my repo/src/main.rs"))
+    );
+  }
+
+  #[test]
+  fn test_normalize_uris() {
+    let mut sarif: sarif::Sarif = serde_json::from_str(
+      r#"{"version":"2.1.0","runs":[{"tool":{"driver":{"name":"clippy"}},"results":[
+        {"message":{"text":"a"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"/root/a b/./src/../main.rs"}}}]},
+        {"message":{"text":"b"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"/usr/include/stdio.h"}}}]},
+        {"message":{"text":"c"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"main.rs","uriBaseId":"SRC"}}}]}
+      ]}]}"#,
+    )
+    .unwrap();
+
+    normalize_uris(&mut sarif, Path::new("/root/a b"));
+    let run = &sarif.runs[0];
+    let uris: Vec<(Option<&str>, Option<&str>)> = run
+      .results
+      .iter()
+      .flatten()
+      .map(|result| {
+        let artifact_location = result.locations.as_ref().unwrap()[0]
+          .physical_location
+          .as_ref()
+          .unwrap()
+          .artifact_location
+          .as_ref()
+          .unwrap();
+        (
+          artifact_location.uri.as_deref(),
+          artifact_location.uri_base_id.as_deref(),
+        )
+      })
+      .collect();
+    assert_eq!(
+      uris,
+      vec![
+        (Some("main.rs"), Some(SRCROOT)),
+        (Some("file:///usr/include/stdio.h"), None),
+        (Some("main.rs"), Some("SRC")),
+      ]
+    );
+    assert_eq!(
+      run.original_uri_base_ids.as_ref().unwrap()[SRCROOT]
+        .uri
+        .as_deref(),
+      Some("file:///root/a%20b/")
+    );
+  }
+}
@@ -0,0 +1,713 @@
+//! Validates SARIF logs against the SARIF 2.1.0 schema and specification.
+//!
+//! Deserializing into [crate::sarif::Sarif] only checks the shape of a log.
+//! [validate] additionally checks the constraints of the JSON schema the
+//! types are generated from (ex. `minimum`, `pattern`, `uniqueItems`, or a
+//! `message` having either a `text` or an `id`) as well as rules of the
+//! specification which cannot be expressed by the schema, such as:
+//!
+//! - `ruleIndex`, `rule.index` and `artifactLocation.index` referring to an
+//!   existing rule or artifact of the run
+//! - a region not ending before it starts
+//! - `uriBaseId` referring to an entry of `run.originalUriBaseIds`
+//! - `ruleId` and `rule.id` being equal when both are present
+//!
+//! Every violation is reported with a JSON pointer to the offending node.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use serde_sarif::validate::validate;
+//!
+//! let log = serde_json::json!({
+//!   "version": "2.1.0",
+//!   "runs": [{
+//!     "tool": { "driver": { "name": "clippy" } },
+//!     "results": [{ "message": { "text": "oops" }, "ruleIndex": 3 }]
+//!   }]
+//! });
+//!
+//! let violations = validate(&log);
+//! assert_eq!(violations[0].pointer, "/runs/0/results/0/ruleIndex");
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+static SCHEMA: OnceLock<Value> = OnceLock::new();
+
+fn sarif_schema() -> &'static Value {
+  SCHEMA.get_or_init(|| {
+    serde_json::from_str(include_str!("schema.json"))
+      .expect("the embedded SARIF schema is valid JSON")
+  })
+}
+
+/// A violation of the SARIF schema or specification
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+  /// A JSON pointer (RFC 6901) to the offending node
+  pub pointer: String,
+  /// A description of the violation
+  pub message: String,
+}
+
+impl fmt::Display for Violation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "#{}: {}", self.pointer, self.message)
+  }
+}
+
+// Returns `pointer` extended with the reference token `token`
+fn join(pointer: &str, token: &str) -> String {
+  format!(
+    "{}/{}",
+    pointer,
+    token.replace('~', "~0").replace('/', "~1")
+  )
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+  match expected {
+    "object" => value.is_object(),
+    "array" => value.is_array(),
+    "string" => value.is_string(),
+    "boolean" => value.is_boolean(),
+    "integer" => value.is_i64() || value.is_u64(),
+    "number" => value.is_number(),
+    "null" => value.is_null(),
+    _ => true,
+  }
+}
+
+fn format_matches(format: &str, value: &str) -> bool {
+  static URI: OnceLock<Regex> = OnceLock::new();
+  static DATE_TIME: OnceLock<Regex> = OnceLock::new();
+  match format {
+    "uri" => URI
+      .get_or_init(|| Regex::new(r"^[a-zA-Z][a-zA-Z0-9+.-]*:").unwrap())
+      .is_match(value),
+    "date-time" => DATE_TIME
+      .get_or_init(|| {
+        Regex::new(
+          r"^\d{4}-\d{2}-\d{2}[Tt]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$",
+        )
+        .unwrap()
+      })
+      .is_match(value),
+    _ => true,
+  }
+}
+
+// Checks a JSON value against the subset of JSON schema draft-07 used by the
+// SARIF schema
+#[derive(Default)]
+struct SchemaValidator {
+  patterns: HashMap<&'static str, Option<Regex>>,
+}
+
+impl SchemaValidator {
+  fn check(
+    &mut self,
+    schema: &'static Value,
+    value: &Value,
+    pointer: &str,
+    violations: &mut Vec<Violation>,
+  ) {
+    let violation = |message: String| Violation {
+      pointer: pointer.to_string(),
+      message,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+      let definition = reference
+        .strip_prefix("#/definitions/")
+        .and_then(|name| sarif_schema()["definitions"].get(name));
+      if let Some(definition) = definition {
+        self.check(definition, value, pointer, violations);
+      }
+      return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+      let expected: Vec<&str> = match expected {
+        Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+        _ => expected.as_str().into_iter().collect(),
+      };
+      if !expected
+        .iter()
+        .any(|expected| type_matches(expected, value))
+      {
+        violations
+          .push(violation(format!("expected {}", expected.join(" or "))));
+        return;
+      }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+      if !allowed.contains(value) {
+        let allowed: Vec<String> =
+          allowed.iter().map(Value::to_string).collect();
+        violations
+          .push(violation(format!("expected one of {}", allowed.join(", "))));
+      }
+    }
+
+    if let Some(number) = value.as_f64() {
+      if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+        if number < minimum {
+          violations.push(violation(format!(
+            "must be greater than or equal to {}",
+            minimum
+          )));
+        }
+      }
+      if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+        if number > maximum {
+          violations.push(violation(format!(
+            "must be less than or equal to {}",
+            maximum
+          )));
+        }
+      }
+    }
+
+    if let Some(string) = value.as_str() {
+      if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+        let regex = self
+          .patterns
+          .entry(pattern)
+          .or_insert_with(|| Regex::new(pattern).ok());
+        if regex.as_ref().is_some_and(|regex| !regex.is_match(string)) {
+          violations
+            .push(violation(format!("does not match the pattern {}", pattern)));
+        }
+      }
+      if let Some(format) = schema.get("format").and_then(Value::as_str) {
+        if !format_matches(format, string) {
+          violations.push(violation(format!("is not a valid {}", format)));
+        }
+      }
+    }
+
+    if let Some(items) = value.as_array() {
+      if let Some(min_items) = schema.get("minItems").and_then(Value::as_u64) {
+        if (items.len() as u64) < min_items {
+          violations.push(violation(format!(
+            "must contain at least {} items",
+            min_items
+          )));
+        }
+      }
+      if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+        let mut seen = HashSet::new();
+        items.iter().enumerate().for_each(|(i, item)| {
+          if !seen.insert(item.to_string()) {
+            violations.push(Violation {
+              pointer: join(pointer, &i.to_string()),
+              message: "duplicates a previous item".to_string(),
+            });
+          }
+        });
+      }
+      if let Some(item_schema) = schema.get("items") {
+        items.iter().enumerate().for_each(|(i, item)| {
+          self.check(
+            item_schema,
+            item,
+            &join(pointer, &i.to_string()),
+            violations,
+          )
+        });
+      }
+    }
+
+    if let Some(object) = value.as_object() {
+      self.check_object(schema, object, pointer, violations);
+    }
+
+    if let Some(schemas) = schema.get("anyOf").and_then(Value::as_array) {
+      if !schemas.iter().any(|schema| self.is_valid(schema, value)) {
+        violations.push(violation(describe_alternatives(schemas, "any")));
+      }
+    }
+
+    if let Some(schemas) = schema.get("oneOf").and_then(Value::as_array) {
+      let valid = schemas
+        .iter()
+        .filter(|schema| self.is_valid(schema, value))
+        .count();
+      if valid != 1 {
+        violations
+          .push(violation(describe_alternatives(schemas, "exactly one")));
+      }
+    }
+  }
+
+  fn check_object(
+    &mut self,
+    schema: &'static Value,
+    object: &Map<String, Value>,
+    pointer: &str,
+    violations: &mut Vec<Violation>,
+  ) {
+    schema
+      .get("required")
+      .and_then(Value::as_array)
+      .into_iter()
+      .flatten()
+      .filter_map(Value::as_str)
+      .filter(|key| !object.contains_key(*key))
+      .for_each(|key| {
+        violations.push(Violation {
+          pointer: pointer.to_string(),
+          message: format!("missing required property `{}`", key),
+        })
+      });
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    object.iter().for_each(|(key, value)| {
+      let pointer = join(pointer, key);
+      match properties.and_then(|properties| properties.get(key)) {
+        Some(property_schema) => {
+          self.check(property_schema, value, &pointer, violations)
+        }
+        None => match schema.get("additionalProperties") {
+          Some(Value::Bool(false)) => violations.push(Violation {
+            pointer,
+            message: "unknown property".to_string(),
+          }),
+          Some(additional @ Value::Object(_)) => {
+            self.check(additional, value, &pointer, violations)
+          }
+          _ => {}
+        },
+      }
+    });
+  }
+
+  fn is_valid(&mut self, schema: &'static Value, value: &Value) -> bool {
+    let mut violations = vec![];
+    self.check(schema, value, "", &mut violations);
+    violations.is_empty()
+  }
+}
+
+// Describes alternatives which only differ by their required properties (ex.
+// `{ "anyOf": [{ "required": ["text"] }, { "required": ["id"] }] }`) in terms of
+// the properties, and other alternatives generically.
+fn describe_alternatives(schemas: &[Value], quantifier: &str) -> String {
+  let required: Option<Vec<String>> = schemas
+    .iter()
+    .map(|schema| {
+      let schema = schema.as_object()?;
+      let required = schema.get("required")?.as_array()?;
+      if schema.len() != 1 {
+        return None;
+      }
+      let keys: Vec<String> = required
+        .iter()
+        .filter_map(Value::as_str)
+        .map(|key| format!("`{}`", key))
+        .collect();
+      Some(keys.join(" and "))
+    })
+    .collect();
+  match required {
+    Some(required) => {
+      format!("must have {} of {}", quantifier, required.join(", "))
+    }
+    None => format!("must match {} of the allowed schemas", quantifier),
+  }
+}
+
+// Context of the run being checked by the specification rules
+struct RunContext<'a> {
+  pointer: String,
+  rule_count: Option<usize>,
+  artifact_count: Option<usize>,
+  uri_base_ids: Option<&'a Map<String, Value>>,
+}
+
+fn check_index(
+  index: Option<&Value>,
+  count: Option<usize>,
+  pointer: &str,
+  kind: &str,
+  run: &RunContext,
+  violations: &mut Vec<Violation>,
+) {
+  let index = match index.and_then(Value::as_i64) {
+    // -1 means the index is not set
+    Some(index) if index >= 0 => index as usize,
+    _ => return,
+  };
+  if index >= count.unwrap_or(0) {
+    violations.push(Violation {
+      pointer: pointer.to_string(),
+      message: format!(
+        "refers to {} {} but {}/{} has {}",
+        kind,
+        index,
+        run.pointer,
+        if kind == "rule" {
+          "tool/driver/rules"
+        } else {
+          "artifacts"
+        },
+        count.map_or("none".to_string(), |count| count.to_string())
+      ),
+    });
+  }
+}
+
+fn check_uri_base_id(
+  object: &Map<String, Value>,
+  pointer: &str,
+  run: &RunContext,
+  violations: &mut Vec<Violation>,
+) {
+  if let (Some(uri_base_id), Some(uri_base_ids)) = (
+    object.get("uriBaseId").and_then(Value::as_str),
+    run.uri_base_ids,
+  ) {
+    if !uri_base_ids.contains_key(uri_base_id) {
+      violations.push(Violation {
+        pointer: join(pointer, "uriBaseId"),
+        message: format!(
+          "`{}` is not defined in {}/originalUriBaseIds",
+          uri_base_id, run.pointer
+        ),
+      });
+    }
+  }
+}
+
+fn check_region(
+  region: &Map<String, Value>,
+  pointer: &str,
+  violations: &mut Vec<Violation>,
+) {
+  let get = |key: &str| region.get(key).and_then(Value::as_i64);
+  let start_line = get("startLine");
+  let end_line = get("endLine");
+  if let (Some(start_line), Some(end_line)) = (start_line, end_line) {
+    if end_line < start_line {
+      violations.push(Violation {
+        pointer: join(pointer, "endLine"),
+        message: format!(
+          "must be greater than or equal to startLine ({})",
+          start_line
+        ),
+      });
+    }
+  }
+  if let (Some(start_column), Some(end_column)) =
+    (get("startColumn"), get("endColumn"))
+  {
+    let single_line = start_line.is_some()
+      && end_line.is_none_or(|end_line| Some(end_line) == start_line);
+    if single_line && end_column < start_column {
+      violations.push(Violation {
+        pointer: join(pointer, "endColumn"),
+        message: format!(
+          "must be greater than or equal to startColumn ({}) on a single line region",
+          start_column
+        ),
+      });
+    }
+  }
+}
+
+// Walks the nodes of a result, checking the references they make into the run
+fn check_result_node(
+  key: Option<&str>,
+  value: &Value,
+  pointer: &str,
+  run: &RunContext,
+  violations: &mut Vec<Violation>,
+) {
+  match value {
+    Value::Object(object) => {
+      match key {
+        Some("artifactLocation") | Some("analysisTarget") => {
+          check_index(
+            object.get("index"),
+            run.artifact_count,
+            &join(pointer, "index"),
+            "artifact",
+            run,
+            violations,
+          );
+          check_uri_base_id(object, pointer, run, violations);
+        }
+        Some("region") | Some("contextRegion") | Some("deletedRegion") => {
+          check_region(object, pointer, violations);
+        }
+        _ => {}
+      }
+      object
+        .iter()
+        // property bags are opaque
+        .filter(|(key, _)| key.as_str() != "properties")
+        .for_each(|(key, value)| {
+          check_result_node(
+            Some(key),
+            value,
+            &join(pointer, key),
+            run,
+            violations,
+          )
+        });
+    }
+    Value::Array(items) => items.iter().enumerate().for_each(|(i, item)| {
+      check_result_node(
+        key,
+        item,
+        &join(pointer, &i.to_string()),
+        run,
+        violations,
+      )
+    }),
+    _ => {}
+  }
+}
+
+fn check_result(
+  value: &Value,
+  pointer: &str,
+  run: &RunContext,
+  violations: &mut Vec<Violation>,
+) {
+  let result = match value.as_object() {
+    Some(result) => result,
+    None => return,
+  };
+  check_index(
+    result.get("ruleIndex"),
+    run.rule_count,
+    &join(pointer, "ruleIndex"),
+    "rule",
+    run,
+    violations,
+  );
+  if let Some(rule) = result.get("rule").and_then(Value::as_object) {
+    // rules of tool extensions are not part of the driver rules
+    if !rule.contains_key("toolComponent") {
+      check_index(
+        rule.get("index"),
+        run.rule_count,
+        &join(&join(pointer, "rule"), "index"),
+        "rule",
+        run,
+        violations,
+      );
+    }
+    if let (Some(rule_id), Some(id)) = (
+      result.get("ruleId").and_then(Value::as_str),
+      rule.get("id").and_then(Value::as_str),
+    ) {
+      if rule_id != id {
+        violations.push(Violation {
+          pointer: join(&join(pointer, "rule"), "id"),
+          message: format!("must be equal to ruleId (`{}`)", rule_id),
+        });
+      }
+    }
+  }
+  check_result_node(None, value, pointer, run, violations);
+}
+
+fn check_run(
+  run: &Map<String, Value>,
+  pointer: &str,
+  violations: &mut Vec<Violation>,
+) {
+  let uri_base_ids = run.get("originalUriBaseIds").and_then(Value::as_object);
+  let context = RunContext {
+    pointer: pointer.to_string(),
+    rule_count: run
+      .get("tool")
+      .and_then(|tool| tool.get("driver"))
+      .and_then(|driver| driver.get("rules"))
+      .and_then(Value::as_array)
+      .map(Vec::len),
+    artifact_count: run
+      .get("artifacts")
+      .and_then(Value::as_array)
+      .map(Vec::len),
+    uri_base_ids,
+  };
+
+  uri_base_ids
+    .into_iter()
+    .flatten()
+    .for_each(|(id, location)| {
+      let pointer = join(&join(pointer, "originalUriBaseIds"), id);
+      if let Some(location) = location.as_object() {
+        if let Some(uri) = location.get("uri").and_then(Value::as_str) {
+          if !uri.ends_with('/') {
+            violations.push(Violation {
+              pointer: join(&pointer, "uri"),
+              message: "must end with a slash".to_string(),
+            });
+          }
+        }
+        check_uri_base_id(location, &pointer, &context, violations);
+      }
+    });
+
+  run
+    .get("results")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .enumerate()
+    .for_each(|(i, result)| {
+      let pointer = join(&join(pointer, "results"), &i.to_string());
+      check_result(result, &pointer, &context, violations);
+    });
+}
+
+/// Returns the violations of the SARIF 2.1.0 schema and specification found in
+/// `log`
+///
+/// The log is validated as JSON rather than as [crate::sarif::Sarif], so that
+/// logs which cannot be deserialized can be diagnosed as well. An empty vector
+/// means the log is valid.
+///
+/// # Arguments
+///
+/// * `log` - The SARIF log to validate
+pub fn validate(log: &Value) -> Vec<Violation> {
+  let mut violations = vec![];
+  SchemaValidator::default().check(sarif_schema(), log, "", &mut violations);
+
+  log
+    .get("runs")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+    .enumerate()
+    .for_each(|(i, run)| {
+      if let Some(run) = run.as_object() {
+        check_run(run, &join("/runs", &i.to_string()), &mut violations);
+      }
+    });
+
+  violations
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn log(run: Value) -> Value {
+    json!({ "version": "2.1.0", "runs": [run] })
+  }
+
+  fn pointers(log: &Value) -> Vec<String> {
+    validate(log)
+      .into_iter()
+      .map(|violation| violation.pointer)
+      .collect()
+  }
+
+  #[test]
+  fn test_validate_valid() {
+    let log = log(json!({
+      "tool": { "driver": { "name": "clippy", "rules": [{ "id": "a" }] } },
+      "originalUriBaseIds": { "SRCROOT": { "uri": "file:///src/" } },
+      "artifacts": [{ "location": { "uri": "lib.rs", "uriBaseId": "SRCROOT" } }],
+      "results": [{
+        "ruleId": "a",
+        "ruleIndex": 0,
+        "message": { "text": "oops" },
+        "locations": [{ "physicalLocation": {
+          "artifactLocation": { "uri": "lib.rs", "uriBaseId": "SRCROOT", "index": 0 },
+          "region": { "startLine": 1, "startColumn": 1, "endColumn": 3 }
+        } }]
+      }]
+    }));
+    assert_eq!(validate(&log), vec![]);
+  }
+
+  #[test]
+  fn test_validate_schema() {
+    let log = json!({
+      "version": "2.0.0",
+      "runs": [{
+        "tool": { "driver": {} },
+        "results": [{ "message": {}, "level": "fatal" }]
+      }]
+    });
+    assert_eq!(
+      validate(&log),
+      vec![
+        Violation {
+          pointer: "/runs/0/results/0/level".to_string(),
+          message: "expected one of \"none\", \"note\", \"warning\", \"error\""
+            .to_string(),
+        },
+        Violation {
+          pointer: "/runs/0/results/0/message".to_string(),
+          message: "must have any of `text`, `id`".to_string(),
+        },
+        Violation {
+          pointer: "/runs/0/tool/driver".to_string(),
+          message: "missing required property `name`".to_string(),
+        },
+        Violation {
+          pointer: "/version".to_string(),
+          message: "expected one of \"2.1.0\"".to_string(),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_validate_specification() {
+    let log = log(json!({
+      "tool": { "driver": { "name": "clippy", "rules": [{ "id": "a" }] } },
+      "originalUriBaseIds": { "SRCROOT": { "uri": "file:///src" } },
+      "results": [{
+        "ruleId": "a",
+        "ruleIndex": 1,
+        "rule": { "id": "b" },
+        "message": { "text": "oops" },
+        "locations": [{ "physicalLocation": {
+          "artifactLocation": { "uri": "lib.rs", "uriBaseId": "ROOT", "index": 0 },
+          "region": { "startLine": 3, "endLine": 2 }
+        } }]
+      }]
+    }));
+    assert_eq!(
+      pointers(&log),
+      vec![
+        "/runs/0/originalUriBaseIds/SRCROOT/uri",
+        "/runs/0/results/0/ruleIndex",
+        "/runs/0/results/0/rule/id",
+        "/runs/0/results/0/locations/0/physicalLocation/artifactLocation/index",
+        "/runs/0/results/0/locations/0/physicalLocation/artifactLocation/uriBaseId",
+        "/runs/0/results/0/locations/0/physicalLocation/region/endLine",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_validate_escapes_pointers() {
+    let log = log(json!({
+      "tool": { "driver": { "name": "clippy" } },
+      "originalUriBaseIds": { "a/b~c": { "uri": "file:///src" } }
+    }));
+    assert_eq!(
+      pointers(&log),
+      vec!["/runs/0/originalUriBaseIds/a~1b~0c/uri"]
+    );
+  }
+}
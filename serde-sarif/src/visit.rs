@@ -0,0 +1,135 @@
+//! Helpers which walk the objects nested inside a [sarif::Result], used by
+//! operations which have to rewrite every reference of a given kind (ex.
+//! re-indexing artifacts when merging runs) or resolve references into the
+//! run.
+
+use crate::sarif;
+
+fn for_each_stack_location_mut<F: FnMut(&mut sarif::Location)>(
+  stack: &mut sarif::Stack,
+  f: &mut F,
+) {
+  stack
+    .frames
+    .iter_mut()
+    .filter_map(|frame| frame.location.as_mut())
+    .for_each(&mut *f);
+}
+
+/// Calls `f` with every [sarif::Location] nested in `result`
+pub(crate) fn for_each_location_mut<F: FnMut(&mut sarif::Location)>(
+  result: &mut sarif::Result,
+  f: &mut F,
+) {
+  result
+    .locations
+    .iter_mut()
+    .flatten()
+    .chain(result.related_locations.iter_mut().flatten())
+    .for_each(&mut *f);
+
+  result
+    .code_flows
+    .iter_mut()
+    .flatten()
+    .flat_map(|code_flow| code_flow.thread_flows.iter_mut())
+    .flat_map(|thread_flow| thread_flow.locations.iter_mut())
+    .for_each(|thread_flow_location| {
+      if let Some(location) = thread_flow_location.location.as_mut() {
+        f(location);
+      }
+      if let Some(stack) = thread_flow_location.stack.as_mut() {
+        for_each_stack_location_mut(stack, f);
+      }
+    });
+
+  result
+    .stacks
+    .iter_mut()
+    .flatten()
+    .for_each(|stack| for_each_stack_location_mut(stack, f));
+
+  result
+    .suppressions
+    .iter_mut()
+    .flatten()
+    .filter_map(|suppression| suppression.location.as_mut())
+    .for_each(&mut *f);
+}
+
+/// Calls `f` with every [sarif::ArtifactLocation] nested in `result`
+pub(crate) fn for_each_artifact_location_mut<
+  F: FnMut(&mut sarif::ArtifactLocation),
+>(
+  result: &mut sarif::Result,
+  f: &mut F,
+) {
+  for_each_location_mut(result, &mut |location| {
+    if let Some(artifact_location) = location
+      .physical_location
+      .as_mut()
+      .and_then(|physical_location| {
+        physical_location.artifact_location.as_mut()
+      })
+    {
+      f(artifact_location);
+    }
+  });
+
+  if let Some(analysis_target) = result.analysis_target.as_mut() {
+    f(analysis_target);
+  }
+
+  result
+    .attachments
+    .iter_mut()
+    .flatten()
+    .for_each(|attachment| f(&mut attachment.artifact_location));
+
+  result
+    .fixes
+    .iter_mut()
+    .flatten()
+    .flat_map(|fix| fix.artifact_changes.iter_mut())
+    .for_each(|artifact_change| f(&mut artifact_change.artifact_location));
+
+  result
+    .provenance
+    .iter_mut()
+    .flat_map(|provenance| provenance.conversion_sources.iter_mut().flatten())
+    .filter_map(|physical_location| {
+      physical_location.artifact_location.as_mut()
+    })
+    .for_each(&mut *f);
+}
+
+/// Returns the id of the rule of `result`, from `ruleId`, `rule.id` or the
+/// rule of `run` referenced by `ruleIndex` / `rule.index`
+///
+/// # Arguments
+///
+/// * `run` - The run `result` belongs to
+/// * `result` - The result whose rule id to return
+pub fn rule_id<'a>(
+  run: &'a sarif::Run,
+  result: &'a sarif::Result,
+) -> Option<&'a str> {
+  result
+    .rule_id
+    .as_deref()
+    .or_else(|| result.rule.as_ref().and_then(|rule| rule.id.as_deref()))
+    .or_else(|| {
+      result
+        .rule_index
+        .or_else(|| result.rule.as_ref().and_then(|rule| rule.index))
+        .and_then(|rule_index| {
+          run
+            .tool
+            .driver
+            .rules
+            .as_ref()
+            .and_then(|rules| rules.get(rule_index as usize))
+        })
+        .map(|rule| rule.id.as_str())
+    })
+}
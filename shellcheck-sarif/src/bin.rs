@@ -45,6 +45,22 @@
 //! After uploading `shellcheck-sarif` output to Github, `shellcheck` diagnostics
 //! are available in GHAS.
 //!
+//! Passing `--fingerprints` populates the `partialFingerprints` of every result
+//! with a hash of its rule, file and source, which lets GHAS keep tracking an
+//! alert when lines are added or removed above it.
+//!
+//! Passing `--artifacts` describes every file referenced by a result in
+//! `run.artifacts`, with its sha-256 hash, length, encoding and mime type.
+//!
+//! Passing `--src-root` makes the URIs of files under the root of the sources
+//! relative to it, with the `uriBaseId` `%SRCROOT%` which is defined in
+//! `run.originalUriBaseIds`, so that results can be matched to another checkout
+//! of the sources. The root defaults to the top level directory of the git
+//! repository, and may be given as `--src-root <PATH>`.
+//!
+//! Fixes suggested by shellcheck are converted into the `fixes` of their
+//! result, and can be applied with `sarif fix`.
+//!
 //! ## Example
 //!
 //! ```yaml
@@ -79,8 +95,13 @@
 
 use anyhow::Result;
 use clap::Parser;
+use serde_sarif::converters::{
+  shellcheck::{ShellcheckConverter, ShellcheckFormat, ShellcheckOptions},
+  Converter, ConverterArgs,
+};
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Read, Write};
+use std::str::FromStr;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -100,6 +121,8 @@ struct Args {
   /// output file; writes to stdout if none is given
   #[arg(short, long)]
   output: Option<std::path::PathBuf>,
+  #[command(flatten)]
+  converter: ConverterArgs,
 }
 
 fn main() -> Result<()> {
@@ -109,18 +132,23 @@ fn main() -> Result<()> {
     Some(path) => Box::new(File::open(path)?) as Box<dyn Read>,
     None => Box::new(std::io::stdin()) as Box<dyn Read>,
   };
-  let reader = BufReader::new(read);
+  let mut reader = BufReader::new(read);
 
-  let format = match args.format {
-    Some(format) => format,
-    None => "json".to_string(),
-  };
+  // anything other than "json1" has historically been treated as "json"
+  let format = args
+    .format
+    .and_then(|format| ShellcheckFormat::from_str(&format).ok())
+    .unwrap_or_default();
 
   let write = match args.output {
     Some(path) => Box::new(File::create(path)?) as Box<dyn Write>,
     None => Box::new(std::io::stdout()) as Box<dyn Write>,
   };
-  let writer = BufWriter::new(write);
+  let mut writer = BufWriter::new(write);
 
-  serde_sarif::converters::shellcheck::parse_to_writer(reader, writer, format)
+  let options = ShellcheckOptions::builder()
+    .format(format)
+    .common(args.converter.options()?)
+    .build();
+  ShellcheckConverter::new(options).convert_to_writer(&mut reader, &mut writer)
 }